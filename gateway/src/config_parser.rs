@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
@@ -11,7 +12,7 @@ use crate::tool::{
     ImplicitToolConfig, StaticToolConfig, ToolCallConfig, ToolChoice, ToolConfig,
     IMPLICIT_TOOL_NAME,
 };
-use crate::variant::VariantConfig;
+use crate::variant::{JsonMode, VariantConfig};
 
 #[derive(Debug, Default)]
 pub struct Config<'c> {
@@ -21,9 +22,89 @@ pub struct Config<'c> {
     pub metrics: HashMap<String, MetricConfig>, // metric name => metric config
     pub tools: HashMap<String, StaticToolConfig>, // tool name => tool config
     pub templates: TemplateConfig<'c>,
+    // Custom MiniJinja delimiters, whitespace mode, shared template directory, and hot-reload
+    // intent from an optional `[template_engine]` section. `None` means "use MiniJinja's
+    // defaults", matching prior behavior.
+    pub template_engine: Option<TemplateEngineConfig>,
+    // Named partial => resolved file path, merged from the `[template_partials]` registry and any
+    // conventional `partials/` directory. Registered into the MiniJinja environment under these
+    // stable names so variant templates can `{% include %}` / `{% extends %}` them.
+    pub template_partials: HashMap<String, PathBuf>,
+    // Dotted config path (e.g. `models.gpt-3.5-turbo.providers.openai.api_key`) => the source
+    // that last set it. Populated during layered loading so that validation errors and tooling
+    // can name the originating source rather than just the logical key.
+    pub(crate) sources: HashMap<String, ConfigSource>,
+    // Generic (glob) model/tool entries consulted when an exact-match lookup misses, from the
+    // `[model_patterns.*]` / `[tool_patterns.*]` sections. Kept in no particular order: lookups
+    // pick the highest-priority match.
+    pub(crate) model_patterns: Vec<GenericEntry<ModelConfig>>,
+    pub(crate) tool_patterns: Vec<GenericEntry<StaticToolConfig>>,
+    // The raw (not yet deserialized) `config` table of each `[tool_patterns.*]` entry, so
+    // `get_tool` can re-derive a `StaticToolConfig` with the actually-requested tool name
+    // substituted for the pattern on each match, rather than reusing the single `StaticToolConfig`
+    // loaded (with the pattern string itself as its name) at config-load time. `ModelConfig` has
+    // no analogous name field for a model to carry, so `model_patterns` needs no equivalent.
+    pub(crate) tool_pattern_configs: HashMap<String, toml::Table>,
+    // Compiled grammar for every `[tools.*]` entry with `strict = true`, keyed by tool name. See
+    // `Config::tool_grammar` and `ToolGrammar`. Tools only reachable through a `[tool_patterns.*]`
+    // fallback aren't compiled here, for the same reason `tool_pattern_configs` can't eagerly
+    // resolve them: the actual tool name (and so the cache key) isn't known until request time.
+    pub(crate) tool_grammars: HashMap<String, ToolGrammar>,
+    pub(crate) base_path: PathBuf,
 }
 
-#[derive(Debug, Default, Deserialize)]
+/// A generic (glob) config entry: `pattern` is matched against the requested name (a single `*`
+/// matches any substring), and `priority` breaks ties when more than one pattern matches —
+/// higher priority wins.
+#[derive(Debug)]
+pub(crate) struct GenericEntry<T> {
+    pub pattern: String,
+    pub priority: i64,
+    pub config: T,
+}
+
+/// Where a resolved config value came from. Used for `Config::sources`/`describe_value` so
+/// validation errors and tooling can point at the originating layer rather than just the logical
+/// key path. The variant order is not a precedence order: an `Include` can sit below the primary
+/// file (a `config/*` entry) or above it (the user config file, an `include`d file).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// The primary config file, used without an explicit `--config` argument.
+    Base,
+    /// The primary config file, as named by an explicit `--config`/CLI argument.
+    CliArg,
+    /// A file merged in ahead of or alongside the primary config (a `config/*.toml` entry or the
+    /// `$TENSORZERO_USER_CONFIG` file).
+    Include(PathBuf),
+    /// A `TENSORZERO_`-prefixed environment variable override.
+    Env,
+}
+
+impl std::fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigSource::Base => write!(f, "the base config file"),
+            ConfigSource::CliArg => write!(f, "the config file passed on the command line"),
+            ConfigSource::Include(path) => write!(f, "{}", path.display()),
+            ConfigSource::Env => write!(f, "an environment variable override"),
+        }
+    }
+}
+
+/// A minimal glob match supporting a single `*` wildcard, e.g. `openai::*` matching
+/// `openai::gpt-4o`.
+fn glob_matches(pattern: &str, candidate: &str) -> bool {
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => {
+            candidate.starts_with(prefix)
+                && candidate.ends_with(suffix)
+                && candidate.len() >= prefix.len() + suffix.len()
+        }
+        None => pattern == candidate,
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct GatewayConfig {
     pub bind_address: Option<std::net::SocketAddr>,
@@ -31,7 +112,7 @@ pub struct GatewayConfig {
     pub disable_observability: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct MetricConfig {
     pub r#type: MetricConfigType,
@@ -39,20 +120,110 @@ pub struct MetricConfig {
     pub level: MetricConfigLevel,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum MetricConfigType {
     Boolean,
     Float,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum MetricConfigOptimize {
     Min,
     Max,
 }
 
+/// An optional `[template_engine]` section describing the MiniJinja environment that should be
+/// used to render `system_template`/`user_template`/`assistant_template` files: custom
+/// block/variable/comment delimiters and a whitespace-handling mode, plus a shared template
+/// directory.
+///
+/// Parsed, validated, and dumped via [`Config::dump`], and passed through to
+/// `TemplateConfig::initialize` (in `crate::minijinja_util`) at the end of
+/// [`Config::load_from_toml`], which applies `block_start`/`variable_start`/`comment_start`/
+/// `whitespace_mode` to the `Environment`'s syntax before any template is compiled. `directory` is
+/// handled entirely in this module (see [`Config::get_templates`]) rather than passed through,
+/// since it only affects which paths get registered, not how they're rendered.
+///
+/// There's no hot-reload flag here: picking up edited template files without restarting the
+/// gateway would mean re-initializing `TemplateConfig` from the gateway's main loop, which is
+/// outside this module's and this section's scope, so it isn't modeled as a config field.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateEngineConfig {
+    #[serde(default)]
+    pub block_start: Option<String>,
+    #[serde(default)]
+    pub block_end: Option<String>,
+    #[serde(default)]
+    pub variable_start: Option<String>,
+    #[serde(default)]
+    pub variable_end: Option<String>,
+    #[serde(default)]
+    pub comment_start: Option<String>,
+    #[serde(default)]
+    pub comment_end: Option<String>,
+    #[serde(default)]
+    pub whitespace_mode: WhitespaceMode,
+    // A directory (relative to the config file) scanned for `*.minijinja` files in addition to
+    // the ones referenced directly by `system_template`/`user_template`/`assistant_template`.
+    #[serde(default)]
+    pub directory: Option<PathBuf>,
+    // Per-variant delimiter overrides (as opposed to one set for the whole engine) are not
+    // implemented: there is no per-variant equivalent of this section, and
+    // `UninitializedVariantConfig` (in `crate::variant`) has no field to carry one.
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WhitespaceMode {
+    #[default]
+    Preserve,
+    TrimBlocks,
+    LstripBlocks,
+}
+
+impl TemplateEngineConfig {
+    /// Each custom delimiter pair (block/variable/comment start+end) must be specified together,
+    /// and no two configured delimiters may be identical, since MiniJinja would then be unable to
+    /// tell them apart while lexing a template.
+    fn validate(&self) -> Result<(), Error> {
+        let pairs = [
+            ("block", &self.block_start, &self.block_end),
+            ("variable", &self.variable_start, &self.variable_end),
+            ("comment", &self.comment_start, &self.comment_end),
+        ];
+        for (name, start, end) in pairs {
+            if start.is_some() != end.is_some() {
+                return Err(Error::Config {
+                    message: format!(
+                        "Invalid Config: `template_engine.{name}_start`/`{name}_end` must both be specified, or neither"
+                    ),
+                });
+            }
+        }
+
+        let delimiters: Vec<&String> = pairs
+            .iter()
+            .flat_map(|(_, start, end)| [start.as_ref(), end.as_ref()])
+            .flatten()
+            .collect();
+        for (i, a) in delimiters.iter().enumerate() {
+            for b in &delimiters[i + 1..] {
+                if a == b {
+                    return Err(Error::Config {
+                        message: format!(
+                            "Invalid Config: `template_engine` delimiter `{a}` is used more than once"
+                        ),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum MetricConfigLevel {
@@ -68,10 +239,179 @@ impl std::fmt::Display for MetricConfigLevel {
     }
 }
 
+/// View of a resolved [`StaticToolConfig`] for [`Config::dump`]. `StaticToolConfig` is defined
+/// outside this module and doesn't implement `Serialize`, and its `parameters` field
+/// (`JSONSchemaFromPath`) doesn't expose the resolved schema back out once built, so the schema
+/// itself isn't reported here — every tool has one (it's not `Option`), so there's nothing
+/// conditional to flag.
+#[derive(Serialize)]
+struct StaticToolConfigDump<'a> {
+    name: &'a str,
+    description: &'a str,
+    strict: bool,
+}
+
+impl<'a> From<&'a StaticToolConfig> for StaticToolConfigDump<'a> {
+    fn from(tool: &'a StaticToolConfig) -> Self {
+        StaticToolConfigDump {
+            name: &tool.name,
+            description: &tool.description,
+            strict: tool.strict,
+        }
+    }
+}
+
+/// View of a resolved [`ToolConfig`] (one entry of a [`ToolCallConfig`]'s `tools_available`).
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ToolConfigDump<'a> {
+    Static(StaticToolConfigDump<'a>),
+    Implicit,
+}
+
+impl<'a> From<&'a ToolConfig> for ToolConfigDump<'a> {
+    fn from(tool: &'a ToolConfig) -> Self {
+        match tool {
+            ToolConfig::Static(tool) => ToolConfigDump::Static(tool.into()),
+            ToolConfig::Implicit(_) => ToolConfigDump::Implicit,
+        }
+    }
+}
+
+/// View of a resolved [`ToolCallConfig`] — e.g. a JSON function's synthesized implicit-tool call
+/// config — for [`Config::dump`]. `ToolChoice` is already `Serialize` (it round-trips through
+/// TOML), so it's reused as-is rather than mirrored here.
+#[derive(Serialize)]
+struct ToolCallConfigDump<'a> {
+    tools_available: Vec<ToolConfigDump<'a>>,
+    tool_choice: &'a ToolChoice,
+    parallel_tool_calls: bool,
+}
+
+impl<'a> From<&'a ToolCallConfig> for ToolCallConfigDump<'a> {
+    fn from(config: &'a ToolCallConfig) -> Self {
+        ToolCallConfigDump {
+            tools_available: config.tools_available.iter().map(Into::into).collect(),
+            tool_choice: &config.tool_choice,
+            parallel_tool_calls: config.parallel_tool_calls,
+        }
+    }
+}
+
+/// View of a resolved [`VariantConfig`] for [`Config::dump`]. Only the `ChatCompletion` variant
+/// exists today; this mirrors `VariantConfig`'s own shape so a new variant kind is a compile
+/// error here too, rather than a silently-incomplete dump.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum VariantConfigDump<'a> {
+    ChatCompletion(ChatCompletionVariantDump<'a>),
+}
+
+/// `JsonMode` is already `Serialize` (it round-trips through TOML), so it's reused as-is.
+/// `has_*_template` reports presence only: the resolved path lives in `Config::get_templates`,
+/// keyed by the same name this config holds, rather than duplicated here.
+#[derive(Serialize)]
+struct ChatCompletionVariantDump<'a> {
+    weight: f64,
+    model: &'a str,
+    json_mode: &'a JsonMode,
+    has_system_template: bool,
+    has_user_template: bool,
+    has_assistant_template: bool,
+}
+
+impl<'a> From<&'a VariantConfig> for VariantConfigDump<'a> {
+    fn from(variant: &'a VariantConfig) -> Self {
+        match variant {
+            VariantConfig::ChatCompletion(params) => {
+                VariantConfigDump::ChatCompletion(ChatCompletionVariantDump {
+                    weight: variant.weight(),
+                    model: &params.model,
+                    json_mode: &params.json_mode,
+                    has_system_template: variant.system_template().is_some(),
+                    has_user_template: variant.user_template().is_some(),
+                    has_assistant_template: variant.assistant_template().is_some(),
+                })
+            }
+        }
+    }
+}
+
+/// View of a resolved [`FunctionConfig`] for [`Config::dump`]. Reports the same
+/// `variants`/`tools`/`tool_choice`/implicit-tool-call data the gateway actually resolves at
+/// request time — not just the function's name — per `Config::dump`'s doc comment.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum FunctionConfigDump<'a> {
+    Chat(FunctionConfigChatDump<'a>),
+    Json(FunctionConfigJsonDump<'a>),
+}
+
+#[derive(Serialize)]
+struct FunctionConfigChatDump<'a> {
+    variants: HashMap<&'a String, VariantConfigDump<'a>>,
+    has_system_schema: bool,
+    has_user_schema: bool,
+    has_assistant_schema: bool,
+    // Each of `function.tools` resolved against the exact-match `[tools.*]` table, rather than
+    // just the tool names. Tools only reachable via a `[tool_patterns.*]` fallback (see
+    // `Config::get_tool`) aren't expanded here, since that lookup returns an owned, per-request
+    // substitution rather than something borrowable for the lifetime of this dump.
+    tools: Vec<StaticToolConfigDump<'a>>,
+    tool_choice: &'a ToolChoice,
+    parallel_tool_calls: bool,
+}
+
+#[derive(Serialize)]
+struct FunctionConfigJsonDump<'a> {
+    variants: HashMap<&'a String, VariantConfigDump<'a>>,
+    has_system_schema: bool,
+    has_user_schema: bool,
+    has_assistant_schema: bool,
+    // The synthesized implicit-tool call config a JSON function is actually resolved to at
+    // request time, expanded in full (including the implicit tool itself).
+    implicit_tool_call_config: ToolCallConfigDump<'a>,
+}
+
 impl<'c> Config<'c> {
+    /// Resolve `function`'s [`FunctionConfigDump`], looking up each of a chat function's
+    /// `tools` by name against `self.tools`.
+    fn dump_function<'a>(&'a self, function: &'a FunctionConfig) -> FunctionConfigDump<'a> {
+        match function {
+            FunctionConfig::Chat(chat) => FunctionConfigDump::Chat(FunctionConfigChatDump {
+                variants: chat
+                    .variants
+                    .iter()
+                    .map(|(name, variant)| (name, variant.into()))
+                    .collect(),
+                has_system_schema: chat.system_schema.is_some(),
+                has_user_schema: chat.user_schema.is_some(),
+                has_assistant_schema: chat.assistant_schema.is_some(),
+                tools: chat
+                    .tools
+                    .iter()
+                    .filter_map(|name| self.tools.get(name))
+                    .map(Into::into)
+                    .collect(),
+                tool_choice: &chat.tool_choice,
+                parallel_tool_calls: chat.parallel_tool_calls,
+            }),
+            FunctionConfig::Json(json) => FunctionConfigDump::Json(FunctionConfigJsonDump {
+                variants: json
+                    .variants
+                    .iter()
+                    .map(|(name, variant)| (name, variant.into()))
+                    .collect(),
+                has_system_schema: json.system_schema.is_some(),
+                has_user_schema: json.user_schema.is_some(),
+                has_assistant_schema: json.assistant_schema.is_some(),
+                implicit_tool_call_config: (&json.implicit_tool_call_config).into(),
+            }),
+        }
+    }
+
     pub fn load() -> Result<Config<'c>, Error> {
         let config_path = UninitializedConfig::get_config_path();
-        let config_table = UninitializedConfig::read_toml_config(&config_path)?;
         let base_path = match PathBuf::from(&config_path).parent() {
             Some(base_path) => base_path.to_path_buf(),
             None => {
@@ -82,11 +422,124 @@ impl<'c> Config<'c> {
                 })
             }
         };
-        let config = Self::load_from_toml(config_table, base_path)?;
+        let primary_source = if std::env::args().nth(1).is_some() {
+            ConfigSource::CliArg
+        } else {
+            ConfigSource::Base
+        };
+
+        let (config_table, sources) =
+            UninitializedConfig::load_layered_table(&config_path, &base_path, primary_source)?;
+        let mut config = Self::load_from_toml(config_table, base_path.clone())?;
+        // `expand_includes` (inside `load_from_toml`) runs after the layered-table merge, so an
+        // `include`d file's keys should win over the layered sources for the same path.
+        let mut merged_sources = sources;
+        merged_sources.extend(config.sources.drain());
+        config.sources = merged_sources;
+
         Ok(config)
     }
 
-    fn load_from_toml(table: toml::Table, base_path: PathBuf) -> Result<Config<'c>, Error> {
+    /// Look up which source last set the value at `key_path` (a dotted config path, e.g.
+    /// `models.gpt-3.5-turbo.providers.azure.endpoint`). Returns `None` for keys that were never
+    /// touched by the layered-loading merge (e.g. a value that only ever came from a default).
+    pub fn describe_value(&self, key_path: &str) -> Option<&ConfigSource> {
+        self.sources.get(key_path)
+    }
+
+    /// Serialize the fully-resolved, validated config to canonical JSON, for the
+    /// `tensorzero config dump` CLI entry point. Lets users confirm what the gateway actually
+    /// sees after defaults, includes, and env overrides are applied, and diff configs in CI.
+    ///
+    /// `functions` and `tools` are expanded in full via [`FunctionConfigDump`] /
+    /// [`StaticToolConfigDump`] — each function's resolved variants, `json_mode`, `tool_choice`,
+    /// and (for JSON functions) the synthesized implicit-tool `ToolCallConfig`, not just names.
+    /// `ModelConfig` is defined outside this module and doesn't implement `Serialize`, so until it
+    /// does, `models` is still reported by name only; `gateway` and `metrics` are dumped in full.
+    pub fn dump(&self) -> Result<serde_json::Value, Error> {
+        let mut models: Vec<&String> = self.models.keys().collect();
+        models.sort();
+
+        // `serde_json::Value::Object` is backed by a `BTreeMap`, so these serialize in
+        // alphabetical key order regardless of the `HashMap`'s own iteration order.
+        let functions: HashMap<&String, FunctionConfigDump> = self
+            .functions
+            .iter()
+            .map(|(name, function)| (name, self.dump_function(function)))
+            .collect();
+
+        let tools: HashMap<&String, StaticToolConfigDump> =
+            self.tools.iter().map(|(name, tool)| (name, tool.into())).collect();
+
+        let mut dump = serde_json::Map::new();
+        dump.insert(
+            "gateway".to_string(),
+            serde_json::to_value(&self.gateway).map_err(|e| Error::Config {
+                message: format!("Failed to serialize config for dump: {e}"),
+            })?,
+        );
+        dump.insert(
+            "metrics".to_string(),
+            serde_json::to_value(&self.metrics).map_err(|e| Error::Config {
+                message: format!("Failed to serialize config for dump: {e}"),
+            })?,
+        );
+        dump.insert(
+            "template_engine".to_string(),
+            serde_json::to_value(&self.template_engine).map_err(|e| Error::Config {
+                message: format!("Failed to serialize config for dump: {e}"),
+            })?,
+        );
+        dump.insert(
+            "models".to_string(),
+            serde_json::to_value(models).expect("Vec<&String> serialization cannot fail"),
+        );
+        dump.insert(
+            "functions".to_string(),
+            serde_json::to_value(functions).map_err(|e| Error::Config {
+                message: format!("Failed to serialize config for dump: {e}"),
+            })?,
+        );
+        dump.insert(
+            "tools".to_string(),
+            serde_json::to_value(tools).map_err(|e| Error::Config {
+                message: format!("Failed to serialize config for dump: {e}"),
+            })?,
+        );
+        let mut template_partials: Vec<&String> = self.template_partials.keys().collect();
+        template_partials.sort();
+        dump.insert(
+            "template_partials".to_string(),
+            serde_json::to_value(template_partials)
+                .expect("Vec<&String> serialization cannot fail"),
+        );
+        Ok(serde_json::Value::Object(dump))
+    }
+
+    fn load_from_toml(mut table: toml::Table, base_path: PathBuf) -> Result<Config<'c>, Error> {
+        let mut include_sources = HashMap::new();
+        expand_includes(&mut table, &base_path, &mut include_sources)?;
+        interpolate_env_vars(&mut table)?;
+        apply_config_item_registry(&mut table);
+        expand_templates(&mut table)?;
+
+        // Captured before `UninitializedConfig::try_from` consumes `table`, so `get_tool` can
+        // later re-deserialize a `[tool_patterns.*]` entry's `config` table with the name that
+        // was actually requested, instead of whichever name was baked in at load time.
+        let tool_pattern_configs: HashMap<String, toml::Table> = table
+            .get("tool_patterns")
+            .and_then(|value| value.as_table())
+            .map(|patterns| {
+                patterns
+                    .iter()
+                    .filter_map(|(pattern, entry)| {
+                        let config_table = entry.as_table()?.get("config")?.as_table()?.clone();
+                        Some((pattern.clone(), config_table))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
         let config = UninitializedConfig::try_from(table)?;
 
         let gateway = config.gateway.unwrap_or_default();
@@ -99,12 +552,57 @@ impl<'c> Config<'c> {
             .map(|(name, config)| config.load(&base_path).map(|c| (name, c)))
             .collect::<Result<HashMap<String, FunctionConfig>, Error>>()?;
 
+        // Resolve each tool's `parameters` schema exactly once (a `Reference` may be a network
+        // fetch, an `Inline` schema a cache write — see `SchemaSource::resolve`), then reuse that
+        // resolution both to compile a `ToolGrammar` for `strict` tools (see `ToolGrammar`) and to
+        // load the tool itself, instead of resolving the same `SchemaSource` twice.
+        let mut tool_grammars = HashMap::new();
         let tools = config
             .tools
             .into_iter()
-            .map(|(name, config)| config.load(&base_path, name.clone()).map(|c| (name, c)))
+            .map(|(name, tool_config)| {
+                let resolved_parameters_path = tool_config.parameters.clone().resolve(&base_path)?;
+                if tool_config.strict {
+                    let grammar = compile_tool_grammar(&resolved_parameters_path, &base_path)?;
+                    tool_grammars.insert(name.clone(), grammar);
+                }
+                tool_config
+                    .load_with_resolved_parameters(resolved_parameters_path, &base_path, name.clone())
+                    .map(|c| (name, c))
+            })
             .collect::<Result<HashMap<String, StaticToolConfig>, Error>>()?;
 
+        let model_patterns = config
+            .model_patterns
+            .into_iter()
+            .map(|(pattern, entry)| GenericEntry {
+                pattern,
+                priority: entry.priority,
+                config: entry.config,
+            })
+            .collect();
+
+        let tool_patterns = config
+            .tool_patterns
+            .into_iter()
+            .map(|(pattern, entry)| {
+                entry
+                    .config
+                    .load(&base_path, pattern.clone())
+                    .map(|config| GenericEntry {
+                        pattern,
+                        priority: entry.priority,
+                        config,
+                    })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        if let Some(template_engine) = &config.template_engine {
+            template_engine.validate()?;
+        }
+
+        let template_partials = resolve_template_partials(&config.template_partials, &base_path);
+
         let mut config = Config {
             gateway,
             models: config.models,
@@ -112,11 +610,27 @@ impl<'c> Config<'c> {
             metrics: config.metrics,
             tools,
             templates,
+            template_engine: config.template_engine,
+            template_partials,
+            sources: include_sources,
+            model_patterns,
+            tool_patterns,
+            tool_pattern_configs,
+            tool_grammars,
+            base_path: base_path.clone(),
         };
 
-        // Initialize the templates
-        let template_paths = config.get_templates(&base_path);
-        config.templates.initialize(template_paths)?;
+        // Initialize the templates: variant templates plus every named partial, so partials are
+        // registered in the MiniJinja environment under the stable names their `{% include %}` /
+        // `{% extends %}` references use. The `[template_engine]` section (if any) is threaded
+        // through here too, so its delimiters/whitespace mode actually apply to the `Environment`
+        // these templates are compiled into, rather than only being parsed/validated/dumped.
+        let mut template_paths = config.get_templates(&base_path);
+        template_paths.extend(config.template_partials.clone());
+        validate_template_partials(&template_paths, &config.template_partials)?;
+        config
+            .templates
+            .initialize(template_paths, config.template_engine.as_ref())?;
 
         // Validate the config
         config.validate()?;
@@ -128,40 +642,15 @@ impl<'c> Config<'c> {
     fn validate(&self) -> Result<(), Error> {
         // Validate each model
         for (model_name, model) in &self.models {
-            // Ensure that the model has at least one provider
-            if model.routing.is_empty() {
-                return Err(Error::Config {
-                    message: format!(
-                        "Invalid Config: `models.{model_name}`: `routing` must not be empty"
-                    ),
-                });
-            }
-
-            // Ensure that routing entries are unique and exist as keys in providers
-            let mut seen_providers = std::collections::HashSet::new();
-            for provider in &model.routing {
-                if !seen_providers.insert(provider) {
-                    return Err(Error::Config {
-                        message: format!("Invalid Config: `models.{model_name}.routing`: duplicate entry `{provider}`"),
-                    });
-                }
-
-                if !model.providers.contains_key(provider) {
-                    return Err(Error::Config {
-                        message: format!("Invalid Config: `models.{model_name}`: `routing` contains entry `{provider}` that does not exist in `providers`"),
-                    });
-                }
-            }
+            Self::validate_model_routing(&format!("models.{model_name}"), model)?;
+        }
 
-            // Validate each provider
-            for provider_name in model.providers.keys() {
-                if !seen_providers.contains(provider_name) {
-                    return Err(Error::Config {
-                        message: format!("Invalid Config: `models.{model_name}`: Provider `{provider_name}` is not listed in `routing`"),
-                    });
-                }
-            }
+        // Validate each generic model pattern entry the same way exact models are validated
+        for entry in &self.model_patterns {
+            Self::validate_model_routing(&format!("model_patterns.{}", entry.pattern), &entry.config)?;
         }
+        Self::validate_unique_priorities("model_patterns", &self.model_patterns)?;
+        Self::validate_unique_priorities("tool_patterns", &self.tool_patterns)?;
 
         // Validate each function
         for (function_name, function) in &self.functions {
@@ -265,6 +754,16 @@ impl<'c> Config<'c> {
                                 }
                             })?;
                         }
+
+                        // Check that a `tool_choice = "specific"` target is actually one of this
+                        // function's tools
+                        if let ToolChoice::Specific(tool_name) = &function.tool_choice {
+                            if !function.tools.contains(tool_name) {
+                                return Err(Error::Config {
+                                    message: format!("Invalid Config: `functions.{function_name}.tool_choice`: tool `{tool_name}` is not present in `functions.{function_name}.tools`"),
+                                });
+                            }
+                        }
                     }
                     FunctionConfig::Json(function) => {
                         // Check that the variant type matches the function type
@@ -378,6 +877,65 @@ impl<'c> Config<'c> {
         Ok(())
     }
 
+    /// Validate a single model's (or generic model pattern's) `routing`/`providers` invariants.
+    /// `label` is the dotted path to use in error messages, e.g. `models.gpt-4` or
+    /// `model_patterns.openai::*`.
+    fn validate_model_routing(label: &str, model: &ModelConfig) -> Result<(), Error> {
+        if model.routing.is_empty() {
+            return Err(Error::Config {
+                message: format!("Invalid Config: `{label}`: `routing` must not be empty"),
+            });
+        }
+
+        let mut seen_providers = std::collections::HashSet::new();
+        for provider in &model.routing {
+            if !seen_providers.insert(provider) {
+                return Err(Error::Config {
+                    message: format!(
+                        "Invalid Config: `{label}.routing`: duplicate entry `{provider}`"
+                    ),
+                });
+            }
+
+            if !model.providers.contains_key(provider) {
+                return Err(Error::Config {
+                    message: format!("Invalid Config: `{label}`: `routing` contains entry `{provider}` that does not exist in `providers`"),
+                });
+            }
+        }
+
+        for provider_name in model.providers.keys() {
+            if !seen_providers.contains(provider_name) {
+                return Err(Error::Config {
+                    message: format!(
+                        "Invalid Config: `{label}`: Provider `{provider_name}` is not listed in `routing`"
+                    ),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reject two generic pattern entries that share a priority: since both could match the same
+    /// name, resolution would be ambiguous. This is a conservative check (it doesn't reason about
+    /// whether the two patterns' glob sets can actually overlap), but a duplicate priority is
+    /// almost always a configuration mistake.
+    fn validate_unique_priorities<T>(section: &str, patterns: &[GenericEntry<T>]) -> Result<(), Error> {
+        let mut seen_priorities = std::collections::HashSet::new();
+        for entry in patterns {
+            if !seen_priorities.insert(entry.priority) {
+                return Err(Error::Config {
+                    message: format!(
+                        "Invalid Config: `{section}`: multiple patterns share priority {} (`{}`); priorities that could both match the same name must be distinct",
+                        entry.priority, entry.pattern
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+
     /// Get a function by name
     pub fn get_function<'a>(&'a self, function_name: &str) -> Result<&'a FunctionConfig, Error> {
         self.functions
@@ -396,22 +954,78 @@ impl<'c> Config<'c> {
             })
     }
 
-    /// Get a tool by name
-    pub fn get_tool<'a>(&'a self, tool_name: &str) -> Result<&'a StaticToolConfig, Error> {
-        self.tools.get(tool_name).ok_or_else(|| Error::UnknownTool {
-            name: tool_name.to_string(),
-        })
+    /// Get a tool by name. An exact `[tools.NAME]` entry is borrowed as-is; otherwise the
+    /// highest-priority `[tool_patterns.*]` entry whose glob matches `tool_name` is re-loaded
+    /// with `tool_name` substituted for the pattern, so two different names matching the same
+    /// wildcard get distinct tool configs instead of both getting the pattern string itself
+    /// baked in as their name.
+    pub fn get_tool<'a>(&'a self, tool_name: &str) -> Result<Cow<'a, StaticToolConfig>, Error> {
+        if let Some(tool) = self.tools.get(tool_name) {
+            return Ok(Cow::Borrowed(tool));
+        }
+
+        let pattern = self
+            .tool_patterns
+            .iter()
+            .filter(|entry| glob_matches(&entry.pattern, tool_name))
+            .max_by_key(|entry| entry.priority)
+            .ok_or_else(|| Error::UnknownTool {
+                name: tool_name.to_string(),
+            })?
+            .pattern
+            .clone();
+
+        let raw_config = self.tool_pattern_configs.get(&pattern).ok_or_else(|| {
+            Error::Config {
+                message: format!(
+                    "Invalid Config: missing raw config for `tool_patterns.{pattern}`"
+                ),
+            }
+        })?;
+        let uninitialized: UninitializedToolConfig =
+            raw_config.clone().try_into().map_err(|e| Error::Config {
+                message: format!("Failed to parse config:\n{e}\nin `tool_patterns.{pattern}`"),
+            })?;
+        let substituted = uninitialized.load(&self.base_path, tool_name.to_string())?;
+
+        Ok(Cow::Owned(substituted))
+    }
+
+    /// Get the compiled grammar for a `strict = true` `[tools.*]` entry, if any. This is the
+    /// closest this module can get to the `StaticToolConfig::grammar()` helper a variant would
+    /// ideally call: `StaticToolConfig` is defined in `crate::tool`, so it can't carry an extra
+    /// field or method here. Only covers exact `[tools.*]` entries — a tool only reachable
+    /// through a `[tool_patterns.*]` fallback isn't compiled eagerly (see `tool_grammars`'s field
+    /// comment) and so has no entry here.
+    pub fn tool_grammar(&self, tool_name: &str) -> Option<&ToolGrammar> {
+        self.tool_grammars.get(tool_name)
     }
 
     /// Get a model by name
     pub fn get_model<'a>(&'a self, model_name: &str) -> Result<&'a ModelConfig, Error> {
-        self.models
-            .get(model_name)
+        if let Some(model) = self.models.get(model_name) {
+            return Ok(model);
+        }
+        Self::best_pattern_match(&self.model_patterns, model_name)
             .ok_or_else(|| Error::UnknownModel {
                 name: model_name.to_string(),
             })
     }
 
+    /// Among `patterns` whose glob matches `name`, return a borrowed reference to the
+    /// highest-priority match's config (ties are rejected at validation time, so any one is
+    /// representative). Used for `model_patterns` only: unlike `StaticToolConfig`, `ModelConfig`
+    /// has no name-shaped field for `name` to be substituted into, so the single loaded config is
+    /// correct for every name the pattern matches. See `get_tool` for the tool-pattern case,
+    /// which does need per-match substitution.
+    fn best_pattern_match<'a, T>(patterns: &'a [GenericEntry<T>], name: &str) -> Option<&'a T> {
+        patterns
+            .iter()
+            .filter(|entry| glob_matches(&entry.pattern, name))
+            .max_by_key(|entry| entry.priority)
+            .map(|entry| &entry.config)
+    }
+
     /// Get all templates from the config
     /// The HashMap returned is a mapping from the path as given in the TOML file
     /// (relative to the directory containing the TOML file) to the path on the filesystem.
@@ -440,8 +1054,114 @@ impl<'c> Config<'c> {
                 }
             }
         }
+
+        // Also pick up every `*.minijinja` file under `[template_engine].directory`, so a shared
+        // template directory doesn't require listing each file individually on a variant.
+        if let Some(template_engine) = &self.template_engine {
+            if let Some(directory) = &template_engine.directory {
+                let full_directory = base_path.as_ref().join(directory);
+                if let Ok(entries) = std::fs::read_dir(&full_directory) {
+                    for entry in entries.flatten() {
+                        let path = entry.path();
+                        if path.extension().and_then(|ext| ext.to_str()) == Some("minijinja") {
+                            if let Ok(relative) = path.strip_prefix(base_path.as_ref()) {
+                                templates.insert(relative.to_string_lossy().to_string(), path);
+                            }
+                        }
+                    }
+                }
+            }
+        }
         templates
     }
+
+    /// Set `key_path` (a dotted path, e.g.
+    /// `functions.generate_draft.variants.openai_promptA.json_mode`) to `value` in the config
+    /// file at `config_path`, preserving comments, key ordering, and formatting everywhere else
+    /// in the file.
+    ///
+    /// `value` accepts anything that converts to a `toml_edit::Value` (strings, numbers, bools,
+    /// arrays, ...), so callers like the tuning subsystem or an operator script can write
+    /// `Config::set(path, "gpt-4o")` or `Config::set(path, 0.9)` directly instead of constructing
+    /// a `toml_edit::Value` by hand.
+    ///
+    /// The edit is applied to an in-memory `toml_edit` document and the resulting document is
+    /// re-run through [`Config::load_from_toml`] before anything is written to disk, so an
+    /// invalid edit (an unknown field, an empty `routing`, a now-missing `output_schema`, ...) is
+    /// rejected atomically instead of corrupting the file.
+    pub fn set<V: Into<toml_edit::Value>>(
+        config_path: &str,
+        key_path: &str,
+        value: V,
+    ) -> Result<(), Error> {
+        Self::update_configuration(config_path, key_path, value.into())
+    }
+
+    /// The `toml_edit::Value`-typed form of [`Config::set`]; kept as its own entry point for
+    /// callers that already have a `toml_edit::Value` in hand (e.g. one parsed out of another
+    /// document) rather than a Rust primitive.
+    pub fn update_configuration(
+        config_path: &str,
+        key_path: &str,
+        value: toml_edit::Value,
+    ) -> Result<(), Error> {
+        let contents = std::fs::read_to_string(config_path).map_err(|_| Error::Config {
+            message: format!("Failed to read config file: {config_path}"),
+        })?;
+        let mut document =
+            contents
+                .parse::<toml_edit::DocumentMut>()
+                .map_err(|e| Error::Config {
+                    message: format!("Failed to parse config file `{config_path}`: {e}"),
+                })?;
+
+        set_nested_toml_edit(document.as_table_mut(), key_path, value)?;
+
+        let base_path = Path::new(config_path)
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let edited_table = document
+            .to_string()
+            .parse::<toml::Table>()
+            .map_err(|e| Error::Config {
+                message: format!("Failed to parse edited config `{config_path}`: {e}"),
+            })?;
+        Self::load_from_toml(edited_table, base_path)?;
+
+        std::fs::write(config_path, document.to_string()).map_err(|_| Error::Config {
+            message: format!("Failed to write config file: {config_path}"),
+        })?;
+        Ok(())
+    }
+}
+
+/// Walk (creating as needed) the intermediate tables named by `key_path`'s dotted segments and set
+/// the final segment to `value`. Mirrors Starship's `handle_update_configuration` loop: indexing
+/// into a key that already holds a non-table value is rejected rather than silently overwritten.
+fn set_nested_toml_edit(
+    mut table: &mut toml_edit::Table,
+    key_path: &str,
+    value: toml_edit::Value,
+) -> Result<(), Error> {
+    let mut segments = key_path.split('.').peekable();
+    while let Some(segment) = segments.next() {
+        if segments.peek().is_none() {
+            table.insert(segment, toml_edit::Item::Value(value));
+            return Ok(());
+        }
+        let entry = table
+            .entry(segment)
+            .or_insert(toml_edit::Item::Table(toml_edit::Table::new()));
+        table = entry.as_table_mut().ok_or_else(|| Error::Config {
+            message: format!(
+                "Invalid Config: `{segment}` in `{key_path}` does not refer to a table"
+            ),
+        })?;
+    }
+    Err(Error::Config {
+        message: "Invalid Config: empty key path".to_string(),
+    })
 }
 
 /// This struct is used to deserialize the TOML config file
@@ -461,6 +1181,29 @@ struct UninitializedConfig {
     pub metrics: HashMap<String, MetricConfig>, // metric name => metric config
     #[serde(default)]
     pub tools: HashMap<String, UninitializedToolConfig>, // tool name => tool config
+    // Generic (glob) model/tool entries, e.g. `[model_patterns."openai::*"]`, consulted by
+    // `Config::get_model`/`get_tool` when an exact-match lookup misses.
+    #[serde(default)]
+    pub model_patterns: HashMap<String, UninitializedGenericEntry<ModelConfig>>,
+    #[serde(default)]
+    pub tool_patterns: HashMap<String, UninitializedGenericEntry<UninitializedToolConfig>>,
+    // Named `template_engine` rather than `templates` to avoid colliding with the top-level
+    // `[templates.NAME]` / `[[template-applications]]` DRY config-templating keys, which
+    // `expand_templates` has already consumed and removed by the time this struct is deserialized.
+    #[serde(default)]
+    pub template_engine: Option<TemplateEngineConfig>,
+    // `[template_partials]`: name => path (relative to the config file) of a shared MiniJinja
+    // partial, e.g. `"shared/tool_preamble" = "partials/tool_preamble.minijinja"`.
+    #[serde(default)]
+    pub template_partials: HashMap<String, PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UninitializedGenericEntry<T> {
+    #[serde(default)]
+    priority: i64,
+    config: T,
 }
 
 impl UninitializedConfig {
@@ -475,51 +1218,998 @@ impl UninitializedConfig {
         }
     }
 
-    /// Read a file from the file system and parse it as TOML
+    /// Read a config file from the file system, parsing it as TOML, YAML, or JSON based on its
+    /// extension (anything else is treated as TOML, preserving prior behavior).
     fn read_toml_config(path: &str) -> Result<toml::Table, Error> {
-        std::fs::read_to_string(path)
-            .map_err(|_| Error::Config {
-                message: format!("Failed to read config file: {path}"),
-            })?
-            .parse::<toml::Table>()
-            .map_err(|_| Error::Config {
+        let contents = std::fs::read_to_string(path).map_err(|_| Error::Config {
+            message: format!("Failed to read config file: {path}"),
+        })?;
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some("yaml") | Some("yml") => {
+                let value: serde_yaml::Value =
+                    serde_yaml::from_str(&contents).map_err(|e| Error::Config {
+                        message: format!("Failed to parse config file as valid YAML: {path}\n{e}"),
+                    })?;
+                yaml_value_to_toml_table(path, value)
+            }
+            Some("json") => {
+                let value: serde_json::Value =
+                    serde_json::from_str(&contents).map_err(|e| Error::Config {
+                        message: format!("Failed to parse config file as valid JSON: {path}\n{e}"),
+                    })?;
+                json_value_to_toml_table(path, value)
+            }
+            _ => contents.parse::<toml::Table>().map_err(|_| Error::Config {
                 message: format!("Failed to parse config file as valid TOML: {path}"),
-            })
+            }),
+        }
     }
-}
 
-/// Deserialize a TOML table into `UninitializedConfig`
-impl TryFrom<toml::Table> for UninitializedConfig {
-    type Error = Error;
+    /// Whether `path` has a file extension this loader knows how to merge/parse, used when
+    /// scanning the `config/` directory for files to layer in.
+    fn is_supported_config_file(path: &Path) -> bool {
+        matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("toml") | Some("yaml") | Some("yml") | Some("json")
+        )
+    }
 
-    fn try_from(table: toml::Table) -> Result<Self, Self::Error> {
-        // NOTE: We'd like to use `serde_path_to_error` here but it has a bug with enums:
-        //       https://github.com/dtolnay/path-to-error/issues/1
-        match table.try_into() {
-            Ok(config) => Ok(config),
-            Err(e) => Err(Error::Config {
-                message: format!("Failed to parse config:\n{e}"),
-            }),
+    /// The directory (relative to `base_path`) whose `*.toml`/`*.yaml`/`*.yml`/`*.json` files
+    /// are merged in ahead of the primary config file, lowest precedence first.
+    const CONFIG_DIR: &'static str = "config";
+
+    /// Environment variable naming an optional user-level config file, merged in just below
+    /// environment variable overrides.
+    const USER_CONFIG_ENV_VAR: &'static str = "TENSORZERO_USER_CONFIG";
+
+    /// Prefix identifying environment variables that override config keys directly, e.g.
+    /// `TENSORZERO_GATEWAY__BIND_ADDRESS=0.0.0.0:4000`.
+    const ENV_OVERRIDE_PREFIX: &'static str = "TENSORZERO_";
+
+    /// Build the fully-merged config table from all configured sources, in increasing
+    /// precedence order:
+    ///
+    /// 1. every `*.toml`/`*.yaml`/`*.yml`/`*.json` file in `<base_path>/config/`, merged in
+    ///    filename order
+    /// 2. the explicit (or default) config file at `config_path`
+    /// 3. a user-level config file named by `$TENSORZERO_USER_CONFIG`, if set
+    /// 4. environment variables of the form `TENSORZERO_A__B__C=value`, mapped onto `a.b.c`
+    ///
+    /// Tables are deep-merged key by key; scalars and arrays are replaced wholesale by whichever
+    /// source defines them last. Returns the merged table alongside a map from dotted config
+    /// path to the source that last set it, so validation errors can later name where a bad
+    /// value came from.
+    fn load_layered_table(
+        config_path: &str,
+        base_path: &Path,
+        primary_source: ConfigSource,
+    ) -> Result<(toml::Table, HashMap<String, ConfigSource>), Error> {
+        let mut merged = toml::Table::new();
+        let mut sources = HashMap::new();
+
+        let config_dir = base_path.join(Self::CONFIG_DIR);
+        if config_dir.is_dir() {
+            let mut entries: Vec<PathBuf> = std::fs::read_dir(&config_dir)
+                .map_err(|e| Error::Config {
+                    message: format!(
+                        "Failed to read config directory `{}`: {e}",
+                        config_dir.display()
+                    ),
+                })?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| Self::is_supported_config_file(path))
+                .collect();
+            entries.sort();
+            for path in entries {
+                let table = Self::read_toml_config(&path.to_string_lossy())?;
+                Self::merge_table(
+                    &mut merged,
+                    table,
+                    &ConfigSource::Include(path.clone()),
+                    "",
+                    &mut sources,
+                );
+            }
         }
-    }
-}
 
-#[derive(Debug, Deserialize)]
-#[serde(tag = "type")]
-#[serde(rename_all = "lowercase")]
-#[serde(deny_unknown_fields)]
-enum UninitializedFunctionConfig {
-    Chat(UninitializedFunctionConfigChat),
-    Json(UninitializedFunctionConfigJson),
-}
+        let primary = Self::read_toml_config(config_path)?;
+        Self::merge_table(&mut merged, primary, &primary_source, "", &mut sources);
+
+        if let Ok(user_config_path) = std::env::var(Self::USER_CONFIG_ENV_VAR) {
+            let table = Self::read_toml_config(&user_config_path)?;
+            Self::merge_table(
+                &mut merged,
+                table,
+                &ConfigSource::Include(PathBuf::from(&user_config_path)),
+                "",
+                &mut sources,
+            );
+        }
 
-#[derive(Debug, Deserialize)]
+        let env_overlay = Self::env_overlay();
+        Self::merge_table(&mut merged, env_overlay, &ConfigSource::Env, "", &mut sources);
+
+        Ok((merged, sources))
+    }
+
+    /// Deep-merge `other` into `base`: tables are merged key by key (recursively); any other
+    /// value (scalar or array) in `other` replaces the corresponding value in `base`. Records
+    /// `source` against every dotted leaf path that `other` sets.
+    fn merge_table(
+        base: &mut toml::Table,
+        other: toml::Table,
+        source: &ConfigSource,
+        prefix: &str,
+        sources: &mut HashMap<String, ConfigSource>,
+    ) {
+        for (key, value) in other {
+            let path = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{prefix}.{key}")
+            };
+            match (base.get_mut(&key), value) {
+                (Some(toml::Value::Table(base_table)), toml::Value::Table(other_table)) => {
+                    Self::merge_table(base_table, other_table, source, &path, sources);
+                }
+                (_, toml::Value::Table(other_table)) => {
+                    let mut new_table = toml::Table::new();
+                    Self::merge_table(&mut new_table, other_table, source, &path, sources);
+                    base.insert(key, toml::Value::Table(new_table));
+                }
+                (_, value) => {
+                    sources.insert(path, source.clone());
+                    base.insert(key, value);
+                }
+            }
+        }
+    }
+
+    /// Build a `toml::Table` overlay from `TENSORZERO_`-prefixed environment variables.
+    ///
+    /// `TENSORZERO_MODELS__GPT4__PROVIDERS__OPENAI__API_KEY=sk-...` becomes the nested path
+    /// `models.gpt4.providers.openai.api_key`.
+    fn env_overlay() -> toml::Table {
+        let mut overlay = toml::Table::new();
+        for (key, value) in std::env::vars() {
+            if key == Self::USER_CONFIG_ENV_VAR {
+                // Not a config override: this variable's *value* is a path to another config
+                // file (merged in by `load_layered_table` above), not a `key=value` assignment.
+                continue;
+            }
+            let Some(rest) = key.strip_prefix(Self::ENV_OVERRIDE_PREFIX) else {
+                continue;
+            };
+            let path: Vec<String> = rest
+                .split("__")
+                .map(|segment| segment.to_lowercase())
+                .collect();
+            Self::set_nested(&mut overlay, &path, value);
+        }
+        overlay
+    }
+
+    /// Insert `value` as a string at the nested dotted `path` within `table`, creating
+    /// intermediate tables as needed.
+    fn set_nested(table: &mut toml::Table, path: &[String], value: String) {
+        let Some((head, rest)) = path.split_first() else {
+            return;
+        };
+        if rest.is_empty() {
+            table.insert(head.clone(), toml::Value::String(value));
+            return;
+        }
+        let entry = table
+            .entry(head.clone())
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        if let toml::Value::Table(nested) = entry {
+            Self::set_nested(nested, rest, value);
+        }
+    }
+}
+
+/// Leaf keys whose string value is a filesystem path resolved later against the root config's
+/// `base_path` (schema files, prompt templates), rather than opaque data. A value under one of
+/// these keys that arrives from an `include`d file must resolve relative to *that file's own*
+/// directory, not the root `base_path` — see [`rebase_included_path`].
+const INCLUDED_PATH_FIELDS: &[&str] = &[
+    "system_schema",
+    "user_schema",
+    "assistant_schema",
+    "output_schema",
+    "parameters",
+    "system_template",
+    "user_template",
+    "assistant_template",
+];
+
+/// If `key` names a path-bearing field and `value` is a plain relative path (not a `://` URL,
+/// which resolves independently of any base path), rewrite it to an absolute path rooted at
+/// `included_dir`. `Path::join` replaces the base entirely when joined with an absolute path, so
+/// once this reaches the root config's `base_path.join(...)` during schema/template resolution it
+/// resolves to the same file regardless of where the root config itself lives.
+fn rebase_included_path(key: &str, value: toml::Value, included_dir: &Path) -> toml::Value {
+    let toml::Value::String(path) = &value else {
+        return value;
+    };
+    if !INCLUDED_PATH_FIELDS.contains(&key) || path.contains("://") {
+        return value;
+    }
+    toml::Value::String(included_dir.join(path).to_string_lossy().into_owned())
+}
+
+/// Expand a top-level `include = ["models/*.toml", "functions/prompts.toml"]` directive: each
+/// glob pattern is resolved relative to `base_path` and every matching file is deep-merged into
+/// `table` (in sorted-path order within each pattern). Two included files that define the same
+/// fully-qualified key fail loudly, naming both source files, rather than silently last-wins;
+/// an included key that merely overrides something already in `table` before includes ran is
+/// fine. The `include` key itself is removed so it doesn't trip `deny_unknown_fields`. Every
+/// leaf key set by an included file is recorded in `sources` as `ConfigSource::Include`.
+fn expand_includes(
+    table: &mut toml::Table,
+    base_path: &Path,
+    sources: &mut HashMap<String, ConfigSource>,
+) -> Result<(), Error> {
+    let patterns: Vec<String> = match table.remove("include") {
+        Some(toml::Value::Array(items)) => items
+            .into_iter()
+            .map(|item| match item {
+                toml::Value::String(pattern) => Ok(pattern),
+                _ => Err(Error::Config {
+                    message: "Invalid Config: `include` entries must be strings".to_string(),
+                }),
+            })
+            .collect::<Result<Vec<_>, Error>>()?,
+        Some(_) => {
+            return Err(Error::Config {
+                message: "Invalid Config: `include` must be an array of glob patterns"
+                    .to_string(),
+            })
+        }
+        None => return Ok(()),
+    };
+
+    // Dotted path => the included file that set it, so a conflict between two *included* files
+    // can be detected without also flagging an include that simply overrides the root table.
+    let mut included_from: HashMap<String, PathBuf> = HashMap::new();
+
+    for pattern in patterns {
+        let mut paths = glob_expand(&base_path.join(&pattern))?;
+        paths.sort();
+        for path in paths {
+            let included_table = UninitializedConfig::read_toml_config(&path.to_string_lossy())?;
+            merge_include(table, included_table, &path, "", &mut included_from, sources)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Expand a single glob `pattern` (a single `*` wildcard within the final path segment) into the
+/// list of matching files. A pattern with no `*` is treated as a literal path that may or may not
+/// exist.
+fn glob_expand(pattern: &Path) -> Result<Vec<PathBuf>, Error> {
+    let parent = pattern.parent().unwrap_or_else(|| Path::new("."));
+    let file_pattern = pattern.file_name().and_then(|f| f.to_str()).unwrap_or("");
+
+    if !file_pattern.contains('*') {
+        return Ok(if pattern.is_file() {
+            vec![pattern.to_path_buf()]
+        } else {
+            vec![]
+        });
+    }
+
+    if !parent.is_dir() {
+        return Ok(vec![]);
+    }
+
+    Ok(std::fs::read_dir(parent)
+        .map_err(|e| Error::Config {
+            message: format!("Failed to read directory `{}`: {e}", parent.display()),
+        })?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|f| f.to_str())
+                .map(|name| glob_matches(file_pattern, name))
+                .unwrap_or(false)
+        })
+        .collect())
+}
+
+/// Deep-merge `other` (read from `source`) into `base`, erroring if a leaf key `other` sets was
+/// already set by a *different* included file (tracked in `included_from`). Path-bearing leaf
+/// values are rebased onto `source`'s own directory (see [`rebase_included_path`]), and every
+/// leaf `other` sets is recorded in `sources` as `ConfigSource::Include(source)`.
+fn merge_include(
+    base: &mut toml::Table,
+    other: toml::Table,
+    source: &Path,
+    prefix: &str,
+    included_from: &mut HashMap<String, PathBuf>,
+    sources: &mut HashMap<String, ConfigSource>,
+) -> Result<(), Error> {
+    let included_dir = source.parent().unwrap_or_else(|| Path::new(""));
+    for (key, value) in other {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(other_table)) => {
+                merge_include(base_table, other_table, source, &path, included_from, sources)?;
+            }
+            (_, toml::Value::Table(other_table)) => {
+                let mut new_table = toml::Table::new();
+                merge_include(&mut new_table, other_table, source, &path, included_from, sources)?;
+                base.insert(key, toml::Value::Table(new_table));
+            }
+            (_, value) => {
+                if let Some(existing_source) = included_from.get(&path) {
+                    if existing_source != source {
+                        return Err(Error::Config {
+                            message: format!(
+                                "Invalid Config: `{path}` is defined by both `{}` and `{}`; please consolidate",
+                                existing_source.display(),
+                                source.display()
+                            ),
+                        });
+                    }
+                }
+                included_from.insert(path.clone(), source.to_path_buf());
+                sources.insert(path, ConfigSource::Include(source.to_path_buf()));
+                let value = rebase_included_path(&key, value, included_dir);
+                base.insert(key, value);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Expand `${VAR}` / `${VAR:-default}` placeholders against the process environment inside every
+/// string value of `table`, recursing into nested tables and arrays so endpoints, deployment
+/// IDs, schema/template paths, and the like can all reference environment variables. A reference
+/// with no default that names an unset variable is an `Error::Config` naming both the variable
+/// and the dotted key path it was found in.
+fn interpolate_env_vars(table: &mut toml::Table) -> Result<(), Error> {
+    interpolate_env_vars_table(table, "")
+}
+
+fn interpolate_env_vars_table(table: &mut toml::Table, prefix: &str) -> Result<(), Error> {
+    for (key, value) in table.iter_mut() {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        interpolate_env_vars_value(value, &path)?;
+    }
+    Ok(())
+}
+
+fn interpolate_env_vars_value(value: &mut toml::Value, path: &str) -> Result<(), Error> {
+    match value {
+        toml::Value::String(s) => {
+            *s = resolve_env_placeholders(s, path)?;
+        }
+        toml::Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                interpolate_env_vars_value(item, &format!("{path}[{index}]"))?;
+            }
+        }
+        toml::Value::Table(sub_table) => {
+            interpolate_env_vars_table(sub_table, path)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Replace every `${VAR}`/`${env:VAR}`/`${VAR:-default}`/`${env:VAR:-default}` occurrence in
+/// `input` with the environment variable's value (or `default`, or an error naming `path` if the
+/// variable is unset and there's no default). The `env:` prefix is accepted as an explicit synonym
+/// for the bare form. Text outside `${...}` placeholders, and an unterminated `${`, is left
+/// untouched.
+fn resolve_env_placeholders(input: &str, path: &str) -> Result<String, Error> {
+    let mut result = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            result.push_str("${");
+            rest = after;
+            continue;
+        };
+        let expr = &after[..end];
+        let (var_name, default) = match expr.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (expr, None),
+        };
+        let var_name = var_name.strip_prefix("env:").unwrap_or(var_name);
+        let resolved = match std::env::var(var_name) {
+            Ok(value) => value,
+            Err(_) => default.map(|default| default.to_string()).ok_or_else(|| Error::Config {
+                message: format!(
+                    "Failed to resolve config:\nenvironment variable `{var_name}` is not set\nin `{path}`\n"
+                ),
+            })?,
+        };
+        result.push_str(&resolved);
+        rest = &after[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Convert a parsed YAML document into a `toml::Table`, so YAML config files flow through the
+/// same merge/validation pipeline as TOML ones. The top level must be a mapping.
+fn yaml_value_to_toml_table(path: &str, value: serde_yaml::Value) -> Result<toml::Table, Error> {
+    match convert_yaml_value(path, value)? {
+        toml::Value::Table(table) => Ok(table),
+        _ => Err(Error::Config {
+            message: format!("Config file `{path}` must contain a top-level mapping"),
+        }),
+    }
+}
+
+fn convert_yaml_value(path: &str, value: serde_yaml::Value) -> Result<toml::Value, Error> {
+    Ok(match value {
+        serde_yaml::Value::Null => {
+            return Err(Error::Config {
+                message: format!("Failed to parse config file `{path}`: TOML has no null type"),
+            })
+        }
+        serde_yaml::Value::Bool(b) => toml::Value::Boolean(b),
+        serde_yaml::Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                toml::Value::Integer(i)
+            } else if let Some(f) = n.as_f64() {
+                toml::Value::Float(f)
+            } else {
+                return Err(Error::Config {
+                    message: format!(
+                        "Failed to parse config file `{path}`: unsupported numeric value"
+                    ),
+                });
+            }
+        }
+        serde_yaml::Value::String(s) => toml::Value::String(s),
+        serde_yaml::Value::Sequence(seq) => toml::Value::Array(
+            seq.into_iter()
+                .map(|v| convert_yaml_value(path, v))
+                .collect::<Result<Vec<_>, Error>>()?,
+        ),
+        serde_yaml::Value::Mapping(map) => {
+            let mut table = toml::Table::new();
+            for (key, value) in map {
+                let key = match key {
+                    serde_yaml::Value::String(key) => key,
+                    _ => {
+                        return Err(Error::Config {
+                            message: format!(
+                                "Failed to parse config file `{path}`: mapping keys must be strings"
+                            ),
+                        })
+                    }
+                };
+                table.insert(key, convert_yaml_value(path, value)?);
+            }
+            toml::Value::Table(table)
+        }
+        serde_yaml::Value::Tagged(tagged) => convert_yaml_value(path, tagged.value)?,
+    })
+}
+
+/// Convert a parsed JSON document into a `toml::Table`, so JSON config files flow through the
+/// same merge/validation pipeline as TOML ones. The top level must be an object.
+fn json_value_to_toml_table(path: &str, value: serde_json::Value) -> Result<toml::Table, Error> {
+    match convert_json_value(path, value)? {
+        toml::Value::Table(table) => Ok(table),
+        _ => Err(Error::Config {
+            message: format!("Config file `{path}` must contain a top-level object"),
+        }),
+    }
+}
+
+fn convert_json_value(path: &str, value: serde_json::Value) -> Result<toml::Value, Error> {
+    Ok(match value {
+        serde_json::Value::Null => {
+            return Err(Error::Config {
+                message: format!("Failed to parse config file `{path}`: TOML has no null type"),
+            })
+        }
+        serde_json::Value::Bool(b) => toml::Value::Boolean(b),
+        serde_json::Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                toml::Value::Integer(i)
+            } else if let Some(f) = n.as_f64() {
+                toml::Value::Float(f)
+            } else {
+                return Err(Error::Config {
+                    message: format!(
+                        "Failed to parse config file `{path}`: unsupported numeric value"
+                    ),
+                });
+            }
+        }
+        serde_json::Value::String(s) => toml::Value::String(s),
+        serde_json::Value::Array(arr) => toml::Value::Array(
+            arr.into_iter()
+                .map(|v| convert_json_value(path, v))
+                .collect::<Result<Vec<_>, Error>>()?,
+        ),
+        serde_json::Value::Object(obj) => {
+            let mut table = toml::Table::new();
+            for (key, value) in obj {
+                table.insert(key, convert_json_value(path, value)?);
+            }
+            toml::Value::Table(table)
+        }
+    })
+}
+
+/// Declarative metadata for a single config key, applied to the raw TOML table before
+/// `UninitializedConfig` is deserialized. This centralizes default population, alias
+/// rewriting, and experimental/deprecation gating that would otherwise be hand-rolled
+/// per field in `validate()` or scattered across `#[serde(default)]` attributes.
+struct ConfigItem {
+    /// Dotted path to the key, where a `*` segment matches any key at that level, e.g.
+    /// `tools.*.strict`.
+    path: &'static str,
+    /// Old key names (within the same table as `path`'s final segment) that should be
+    /// rewritten to the canonical name if the canonical name isn't already present.
+    aliases: &'static [&'static str],
+    /// Value to populate if the key is absent after alias rewriting.
+    default: Option<fn() -> toml::Value>,
+    /// Whether setting this key should emit an "experimental" warning.
+    experimental: bool,
+    /// If set, setting this key emits a deprecation warning with this note.
+    deprecated: Option<&'static str>,
+}
+
+/// The config-item registry. Add an entry here instead of hand-rolling a default/alias/
+/// deprecation check for a new field.
+fn config_item_registry() -> Vec<ConfigItem> {
+    vec![
+        ConfigItem {
+            path: "gateway.disable_observability",
+            aliases: &[],
+            default: Some(|| toml::Value::Boolean(false)),
+            experimental: false,
+            deprecated: None,
+        },
+        ConfigItem {
+            path: "tools.*.strict",
+            aliases: &["strict_json_schema"],
+            default: Some(|| toml::Value::Boolean(false)),
+            experimental: false,
+            deprecated: None,
+        },
+        ConfigItem {
+            path: "model_patterns",
+            aliases: &[],
+            default: None,
+            experimental: true,
+            deprecated: None,
+        },
+        ConfigItem {
+            path: "tool_patterns",
+            aliases: &[],
+            default: None,
+            experimental: true,
+            deprecated: None,
+        },
+        ConfigItem {
+            path: "templates",
+            aliases: &[],
+            default: None,
+            experimental: true,
+            deprecated: None,
+        },
+    ]
+}
+
+/// Apply every entry in `config_item_registry` to `table`: rewrite aliases to their canonical
+/// name, populate defaults for still-absent keys, and emit a warning for any experimental or
+/// deprecated key that ends up set. Sections that don't exist yet are left alone — their
+/// defaults are already handled by `#[serde(default)]`/`Option` on the typed config structs.
+fn apply_config_item_registry(table: &mut toml::Table) {
+    for item in config_item_registry() {
+        let segments: Vec<&str> = item.path.split('.').collect();
+        let Some((leaf, parents)) = segments.split_last() else {
+            continue;
+        };
+        let mut warnings = Vec::new();
+        walk_existing_sections(table, parents, &mut |section: &mut toml::Table| {
+            if !section.contains_key(*leaf) {
+                for alias in item.aliases {
+                    if let Some(value) = section.remove(*alias) {
+                        section.insert((*leaf).to_string(), value);
+                        break;
+                    }
+                }
+            }
+            if !section.contains_key(*leaf) {
+                if let Some(default) = item.default {
+                    section.insert((*leaf).to_string(), default());
+                }
+            }
+            if section.contains_key(*leaf) {
+                if item.experimental {
+                    warnings.push(format!(
+                        "config key `{}` is experimental and may change without notice",
+                        item.path
+                    ));
+                }
+                if let Some(note) = item.deprecated {
+                    warnings.push(format!("config key `{}` is deprecated: {note}", item.path));
+                }
+            }
+        });
+        for warning in warnings {
+            eprintln!("[tensorzero] warning: {warning}");
+        }
+    }
+}
+
+/// Walk `segments` within `table` (fanning out over every key when a segment is `*`), calling
+/// `f` on each table reached at the end of the path. Unlike `apply_template`, this never creates
+/// missing sections — it only touches config that's already present.
+fn walk_existing_sections(
+    table: &mut toml::Table,
+    segments: &[&str],
+    f: &mut dyn FnMut(&mut toml::Table),
+) {
+    match segments.split_first() {
+        None => f(table),
+        Some((segment, rest)) => {
+            if *segment == "*" {
+                for value in table.values_mut() {
+                    if let toml::Value::Table(section) = value {
+                        walk_existing_sections(section, rest, f);
+                    }
+                }
+            } else if let Some(toml::Value::Table(section)) = table.get_mut(*segment) {
+                walk_existing_sections(section, rest, f);
+            }
+        }
+    }
+}
+
+/// A `[[template-applications]]` entry mapping a `[templates.NAME]` section onto every existing
+/// config section whose dotted path matches `target` (where a `*` path segment matches any key).
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TemplateApplication {
+    template: String,
+    target: String,
+}
+
+/// Merge the explicit `[template_partials]` registry with any conventional `partials/` directory
+/// (every `*.minijinja` file there, registered under its file stem) into a single name => resolved
+/// path map. Explicit registry entries win over a same-named file discovered in `partials/`.
+fn resolve_template_partials(
+    registry: &HashMap<String, PathBuf>,
+    base_path: &Path,
+) -> HashMap<String, PathBuf> {
+    let mut partials: HashMap<String, PathBuf> = registry
+        .iter()
+        .map(|(name, path)| (name.clone(), base_path.join(path)))
+        .collect();
+
+    if let Ok(entries) = std::fs::read_dir(base_path.join("partials")) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) == Some("minijinja") {
+                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                    partials.entry(stem.to_string()).or_insert(path);
+                }
+            }
+        }
+    }
+
+    partials
+}
+
+/// Resolve every `{% include "NAME" %}` / `{% extends "NAME" %}` reference in `templates` (variant
+/// templates and partials alike) against `partials` at config-load time, so a typo'd or missing
+/// partial is caught here instead of deferred to inference time.
+fn validate_template_partials(
+    templates: &HashMap<String, PathBuf>,
+    partials: &HashMap<String, PathBuf>,
+) -> Result<(), Error> {
+    for (name, path) in templates {
+        let Ok(contents) = std::fs::read_to_string(path) else {
+            // A missing template file is reported by `TemplateConfig::initialize`, not here.
+            continue;
+        };
+        for reference in find_template_references(&contents) {
+            if !partials.contains_key(&reference) {
+                return Err(Error::Config {
+                    message: format!(
+                        "Invalid Config: template `{name}` references unknown partial `{reference}`"
+                    ),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A plain string scan (not an actual MiniJinja parse) for `{% include "NAME" %}` /
+/// `{% extends "NAME" %}` tags, returning every referenced partial name.
+fn find_template_references(contents: &str) -> Vec<String> {
+    let mut references = Vec::new();
+    for tag in ["{% include \"", "{% extends \""] {
+        let mut rest = contents;
+        while let Some(start) = rest.find(tag) {
+            let after = &rest[start + tag.len()..];
+            let Some(end) = after.find('"') else {
+                break;
+            };
+            references.push(after[..end].to_string());
+            rest = &after[end + 1..];
+        }
+    }
+    references
+}
+
+/// Expand `[templates.NAME]` sections and `[[template-applications]]` entries in-place before
+/// `UninitializedConfig` is deserialized.
+///
+/// For each application, in list order, every existing section matching `target` has any keys
+/// missing from the template filled in (creating the section first if it doesn't exist yet);
+/// concrete keys already present always win over template keys, and since applications are
+/// processed in order and only ever fill gaps, earlier applications take precedence over later
+/// ones when templates disagree. The `templates` and `template-applications` keys are removed
+/// from `table` so they don't trip `deny_unknown_fields` on the real config shape.
+fn expand_templates(table: &mut toml::Table) -> Result<(), Error> {
+    let templates = match table.remove("templates") {
+        Some(toml::Value::Table(templates)) => templates
+            .into_iter()
+            .map(|(name, value)| match value {
+                toml::Value::Table(fields) => Ok((name, fields)),
+                _ => Err(Error::Config {
+                    message: format!("Invalid Config: `templates.{name}` must be a table"),
+                }),
+            })
+            .collect::<Result<HashMap<String, toml::Table>, Error>>()?,
+        Some(_) => {
+            return Err(Error::Config {
+                message: "Invalid Config: `templates` must be a table".to_string(),
+            })
+        }
+        None => HashMap::new(),
+    };
+
+    let applications: Vec<TemplateApplication> = match table.remove("template-applications") {
+        Some(value) => value.try_into().map_err(|e| Error::Config {
+            message: format!("Failed to parse `template-applications`:\n{e}"),
+        })?,
+        None => Vec::new(),
+    };
+
+    for application in &applications {
+        let template = templates.get(&application.template).ok_or_else(|| Error::Config {
+            message: format!(
+                "Invalid Config: `template-applications` references unknown template `{}`",
+                application.template
+            ),
+        })?;
+        let segments: Vec<&str> = application.target.split('.').collect();
+        apply_template(table, &segments, template);
+    }
+
+    Ok(())
+}
+
+/// Recursively walk `segments` within `table`, creating literal intermediate sections as needed
+/// and fanning out over every key when the segment is `*`, filling the template's keys into the
+/// section(s) found at the end of the path.
+fn apply_template(table: &mut toml::Table, segments: &[&str], template: &toml::Table) {
+    let Some((segment, rest)) = segments.split_first() else {
+        return;
+    };
+
+    if *segment == "*" {
+        for value in table.values_mut() {
+            if let toml::Value::Table(section) = value {
+                if rest.is_empty() {
+                    fill_missing_keys(section, template);
+                } else {
+                    apply_template(section, rest, template);
+                }
+            }
+        }
+        return;
+    }
+
+    let entry = table
+        .entry((*segment).to_string())
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    if let toml::Value::Table(section) = entry {
+        if rest.is_empty() {
+            fill_missing_keys(section, template);
+        } else {
+            apply_template(section, rest, template);
+        }
+    }
+}
+
+/// Insert every key from `template` into `section` that `section` does not already define,
+/// recursing into nested tables so that partially-specified sub-tables are filled key-by-key
+/// rather than replaced wholesale.
+fn fill_missing_keys(section: &mut toml::Table, template: &toml::Table) {
+    for (key, value) in template {
+        match (section.get_mut(key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(template_sub)) => {
+                fill_missing_keys(existing, template_sub);
+            }
+            (None, value) => {
+                section.insert(key.clone(), value.clone());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Deserialize a TOML table into `UninitializedConfig`
+impl TryFrom<toml::Table> for UninitializedConfig {
+    type Error = Error;
+
+    fn try_from(table: toml::Table) -> Result<Self, Self::Error> {
+        // NOTE: We'd like to use `serde_path_to_error` here but it has a bug with enums:
+        //       https://github.com/dtolnay/path-to-error/issues/1
+        match table.try_into() {
+            Ok(config) => Ok(config),
+            Err(e) => Err(Error::Config {
+                message: format!("Failed to parse config:\n{e}"),
+            }),
+        }
+    }
+}
+
+/// A `system_schema` / `user_schema` / `assistant_schema` / `output_schema` / tool `parameters`
+/// value: a path to a schema file on disk (the original, and still most common, behavior), the
+/// JSON schema embedded directly in the config as an inline table, or an `https://` URL fetched
+/// once at load time. All three are resolved down to a file path that `JSONSchemaFromPath::new`
+/// can load, so validation and error behavior (`Error::JsonSchema`) is unchanged downstream.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum SchemaSource {
+    Reference(PathBuf),
+    Inline(toml::Table),
+}
+
+impl SchemaSource {
+    fn resolve(self, base_path: &Path) -> Result<PathBuf, Error> {
+        match self {
+            SchemaSource::Reference(path) => {
+                let path_str = path.to_string_lossy();
+                if path_str.starts_with("https://") {
+                    let cache_path = Self::cache_path_for(&path_str);
+                    if cache_path.exists() {
+                        return Ok(cache_path);
+                    }
+                    let body = Self::fetch_https_schema(&path_str)?;
+                    Self::write_cached_schema_body(&cache_path, &body)
+                } else {
+                    Ok(path)
+                }
+            }
+            SchemaSource::Inline(table) => {
+                let body = serde_json::to_string(&toml::Value::Table(table)).map_err(|e| {
+                    Error::JsonSchema {
+                        message: format!("Failed to parse inline JSON Schema: {e}"),
+                    }
+                })?;
+                let cache_path = Self::cache_path_for(&body);
+                if cache_path.exists() {
+                    return Ok(cache_path);
+                }
+                Self::write_cached_schema_body(&cache_path, &body)
+            }
+        }
+    }
+
+    /// Blocking-fetch `url`'s body. `reqwest::blocking` builds and blocks on its own internal
+    /// Tokio runtime, which panics ("Cannot start a runtime from within a runtime") if called
+    /// directly from a thread already driving the gateway's async Axum runtime. `block_in_place`
+    /// is the documented way to run blocking code (including another runtime's `block_on`) from
+    /// inside an async context on a multi-threaded runtime: it hands this thread's other pending
+    /// tasks off to the rest of the pool first, rather than starting a nested runtime on top of
+    /// one that's already driving this thread.
+    fn fetch_https_schema(url: &str) -> Result<String, Error> {
+        tokio::task::block_in_place(|| {
+            reqwest::blocking::get(url)
+                .and_then(|resp| resp.error_for_status())
+                .and_then(|resp| resp.text())
+        })
+        .map_err(|e| Error::JsonSchema {
+            message: format!("Failed to fetch JSON Schema from `{url}`: {e}"),
+        })
+    }
+
+    /// The stable, content-addressed path under the system temp dir that [`Self::resolve`] caches
+    /// `cache_key`'s body under. A pure function of `cache_key` so callers can check for an
+    /// existing cache entry (and skip the network fetch/cache write that would otherwise produce
+    /// it) before doing any I/O.
+    fn cache_path_for(cache_key: &str) -> PathBuf {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        cache_key.hash(&mut hasher);
+        std::env::temp_dir().join(format!("tensorzero_schema_{:x}.json", hasher.finish()))
+    }
+
+    /// Write `body` to `cache_path`, so that repeated loads of the same URL or inline schema
+    /// reuse the cached file (see [`Self::cache_path_for`]) instead of refetching or rewriting it
+    /// on every config load.
+    fn write_cached_schema_body(cache_path: &Path, body: &str) -> Result<PathBuf, Error> {
+        std::fs::write(cache_path, body).map_err(|e| Error::JsonSchema {
+            message: format!("Failed to cache JSON Schema: {e}"),
+        })?;
+        Ok(cache_path.to_path_buf())
+    }
+}
+
+/// A tool's JSON Schema, compiled for opt-in (`strict = true`) grammar-constrained tool-call
+/// generation so a variant can pass it to providers that support grammar/structured-output,
+/// guaranteeing schema-valid arguments instead of best-effort parsing.
+///
+/// "Compiling" a schema here means resolving and parsing it, not translating it into a specific
+/// backend's grammar IR (e.g. GBNF, Lark): no such backend is wired into this crate. Providers
+/// that accept a JSON Schema directly as a structured-output constraint (e.g. OpenAI's Structured
+/// Outputs, several local-inference grammar libraries) can consume `schema` as-is; a future
+/// backend-specific grammar would compile from this same resolved `schema`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ToolGrammar {
+    pub schema: serde_json::Value,
+}
+
+/// Parses `resolved_path` (already resolved via [`SchemaSource::resolve`] by the caller) as JSON
+/// for [`ToolGrammar`]. Deliberately independent of [`JSONSchemaFromPath`] (which doesn't expose
+/// the schema it loads back out) rather than reading the schema file a second time through that
+/// type. Takes an already-resolved path rather than a [`SchemaSource`] so that a `strict` tool's
+/// schema is resolved exactly once per config load — see the call site in
+/// `Config::load_from_toml` — instead of once here and again in
+/// `UninitializedToolConfig::load`'s own resolution, which would double a `Reference`'s network
+/// fetch or an `Inline` schema's cache write.
+fn compile_tool_grammar(resolved_path: &Path, base_path: &Path) -> Result<ToolGrammar, Error> {
+    let resolved_path = base_path.join(resolved_path);
+    let contents = std::fs::read_to_string(&resolved_path).map_err(|e| Error::JsonSchema {
+        message: format!(
+            "Failed to read JSON Schema `{}` for grammar compilation: {e}",
+            resolved_path.display()
+        ),
+    })?;
+    let schema: serde_json::Value = serde_json::from_str(&contents).map_err(|e| Error::JsonSchema {
+        message: format!(
+            "Failed to parse JSON Schema `{}` for grammar compilation: {e}",
+            resolved_path.display()
+        ),
+    })?;
+    Ok(ToolGrammar { schema })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+#[serde(rename_all = "lowercase")]
+#[serde(deny_unknown_fields)]
+enum UninitializedFunctionConfig {
+    Chat(UninitializedFunctionConfigChat),
+    Json(UninitializedFunctionConfigJson),
+}
+
+#[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 struct UninitializedFunctionConfigChat {
     variants: HashMap<String, VariantConfig>, // variant name => variant config
-    system_schema: Option<PathBuf>,
-    user_schema: Option<PathBuf>,
-    assistant_schema: Option<PathBuf>,
+    system_schema: Option<SchemaSource>,
+    user_schema: Option<SchemaSource>,
+    assistant_schema: Option<SchemaSource>,
     #[serde(default)]
     tools: Vec<String>, // tool names
     #[serde(default)]
@@ -532,10 +2222,10 @@ struct UninitializedFunctionConfigChat {
 #[serde(deny_unknown_fields)]
 struct UninitializedFunctionConfigJson {
     variants: HashMap<String, VariantConfig>, // variant name => variant config
-    system_schema: Option<PathBuf>,
-    user_schema: Option<PathBuf>,
-    assistant_schema: Option<PathBuf>,
-    output_schema: PathBuf, // schema is mandatory for JSON functions
+    system_schema: Option<SchemaSource>,
+    user_schema: Option<SchemaSource>,
+    assistant_schema: Option<SchemaSource>,
+    output_schema: SchemaSource, // schema is mandatory for JSON functions
 }
 
 impl UninitializedFunctionConfig {
@@ -544,14 +2234,20 @@ impl UninitializedFunctionConfig {
             UninitializedFunctionConfig::Chat(params) => {
                 let system_schema = params
                     .system_schema
+                    .map(|source| source.resolve(base_path.as_ref()))
+                    .transpose()?
                     .map(|path| JSONSchemaFromPath::new(path, base_path.as_ref()))
                     .transpose()?;
                 let user_schema = params
                     .user_schema
+                    .map(|source| source.resolve(base_path.as_ref()))
+                    .transpose()?
                     .map(|path| JSONSchemaFromPath::new(path, base_path.as_ref()))
                     .transpose()?;
                 let assistant_schema = params
                     .assistant_schema
+                    .map(|source| source.resolve(base_path.as_ref()))
+                    .transpose()?
                     .map(|path| JSONSchemaFromPath::new(path, base_path.as_ref()))
                     .transpose()?;
                 Ok(FunctionConfig::Chat(FunctionConfigChat {
@@ -567,20 +2263,27 @@ impl UninitializedFunctionConfig {
             UninitializedFunctionConfig::Json(params) => {
                 let system_schema = params
                     .system_schema
+                    .map(|source| source.resolve(base_path.as_ref()))
+                    .transpose()?
                     .map(|path| JSONSchemaFromPath::new(path, base_path.as_ref()))
                     .transpose()?;
                 let user_schema = params
                     .user_schema
+                    .map(|source| source.resolve(base_path.as_ref()))
+                    .transpose()?
                     .map(|path| JSONSchemaFromPath::new(path, base_path.as_ref()))
                     .transpose()?;
                 let assistant_schema = params
                     .assistant_schema
+                    .map(|source| source.resolve(base_path.as_ref()))
+                    .transpose()?
                     .map(|path| JSONSchemaFromPath::new(path, base_path.as_ref()))
                     .transpose()?;
+                let output_schema_path = params.output_schema.resolve(base_path.as_ref())?;
                 let output_schema =
-                    JSONSchemaFromPath::new(params.output_schema.clone(), base_path.as_ref())?;
+                    JSONSchemaFromPath::new(output_schema_path.clone(), base_path.as_ref())?;
                 let implicit_tool_output_schema =
-                    JSONSchemaFromPath::new(params.output_schema, base_path.as_ref())?;
+                    JSONSchemaFromPath::new(output_schema_path, base_path.as_ref())?;
                 let implicit_tool = ToolConfig::Implicit(ImplicitToolConfig {
                     parameters: implicit_tool_output_schema,
                 });
@@ -602,10 +2305,10 @@ impl UninitializedFunctionConfig {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct UninitializedToolConfig {
     pub description: String,
-    pub parameters: PathBuf,
+    pub parameters: SchemaSource,
     #[serde(default)]
     pub strict: bool,
 }
@@ -616,7 +2319,21 @@ impl UninitializedToolConfig {
         base_path: P,
         name: String,
     ) -> Result<StaticToolConfig, Error> {
-        let parameters = JSONSchemaFromPath::new(self.parameters, base_path.as_ref())?;
+        let parameters_path = self.parameters.clone().resolve(base_path.as_ref())?;
+        self.load_with_resolved_parameters(parameters_path, base_path, name)
+    }
+
+    /// Like [`Self::load`], but for a `parameters` schema the caller already resolved (see
+    /// `Config::load_from_toml`'s `strict`-tool handling) — reuses that resolution instead of
+    /// resolving `self.parameters` a second time, which would re-fetch a `https://` schema or
+    /// rewrite an inline one's cache file.
+    fn load_with_resolved_parameters<P: AsRef<Path>>(
+        self,
+        parameters_path: PathBuf,
+        base_path: P,
+        name: String,
+    ) -> Result<StaticToolConfig, Error> {
+        let parameters = JSONSchemaFromPath::new(parameters_path, base_path.as_ref())?;
         Ok(StaticToolConfig {
             name,
             description: self.description,
@@ -624,104 +2341,1468 @@ impl UninitializedToolConfig {
             strict: self.strict,
         })
     }
-}
+}
+
+/// A Nelder–Mead simplex optimizer for tuning a function's variant weights and numeric generation
+/// parameters (e.g. `temperature`, `top_p`) against a logged metric, without requiring gradients.
+/// Callers are responsible for wiring up the objective (replaying logged inferences under a
+/// candidate parameter vector and computing the mean metric, negated so the search always
+/// minimizes) and for writing the winning vector back into the config, e.g. via
+/// [`Config::update_configuration`].
+pub mod tuning {
+    use crate::error::Error;
+    use serde::{Deserialize, Serialize};
+    use std::path::Path;
+
+    /// Coefficients and stopping criteria for a Nelder–Mead run. Defaults are the method's
+    /// textbook values (alpha=1, gamma=2, rho=0.5, sigma=0.5).
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct NelderMeadConfig {
+        #[serde(default = "NelderMeadConfig::default_alpha")]
+        pub alpha: f64,
+        #[serde(default = "NelderMeadConfig::default_gamma")]
+        pub gamma: f64,
+        #[serde(default = "NelderMeadConfig::default_rho")]
+        pub rho: f64,
+        #[serde(default = "NelderMeadConfig::default_sigma")]
+        pub sigma: f64,
+        #[serde(default = "NelderMeadConfig::default_max_iterations")]
+        pub max_iterations: usize,
+        #[serde(default = "NelderMeadConfig::default_tolerance")]
+        pub tolerance: f64,
+    }
+
+    impl NelderMeadConfig {
+        fn default_alpha() -> f64 {
+            1.0
+        }
+        fn default_gamma() -> f64 {
+            2.0
+        }
+        fn default_rho() -> f64 {
+            0.5
+        }
+        fn default_sigma() -> f64 {
+            0.5
+        }
+        fn default_max_iterations() -> usize {
+            200
+        }
+        fn default_tolerance() -> f64 {
+            1e-6
+        }
+    }
+
+    impl Default for NelderMeadConfig {
+        fn default() -> Self {
+            Self {
+                alpha: Self::default_alpha(),
+                gamma: Self::default_gamma(),
+                rho: Self::default_rho(),
+                sigma: Self::default_sigma(),
+                max_iterations: Self::default_max_iterations(),
+                tolerance: Self::default_tolerance(),
+            }
+        }
+    }
+
+    /// One parameter vector and the objective value evaluated there.
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    pub struct SimplexVertex {
+        pub point: Vec<f64>,
+        pub value: f64,
+    }
+
+    /// The full state of an in-progress or completed search, persisted to disk between runs (see
+    /// [`SimplexTrace::save`] / [`SimplexTrace::load`]) so a tuning job can resume where it left
+    /// off instead of re-evaluating from scratch.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct SimplexTrace {
+        pub config: NelderMeadConfig,
+        pub iteration: usize,
+        pub vertices: Vec<SimplexVertex>,
+        pub best: SimplexVertex,
+    }
+
+    impl SimplexTrace {
+        pub fn save(&self, path: &Path) -> Result<(), Error> {
+            let json = serde_json::to_string_pretty(self).map_err(|e| Error::Config {
+                message: format!("Failed to serialize tuning trace: {e}"),
+            })?;
+            std::fs::write(path, json).map_err(|e| Error::Config {
+                message: format!("Failed to write tuning trace to `{}`: {e}", path.display()),
+            })
+        }
+
+        pub fn load(path: &Path) -> Result<Self, Error> {
+            let contents = std::fs::read_to_string(path).map_err(|e| Error::Config {
+                message: format!("Failed to read tuning trace from `{}`: {e}", path.display()),
+            })?;
+            serde_json::from_str(&contents).map_err(|e| Error::Config {
+                message: format!("Failed to parse tuning trace from `{}`: {e}", path.display()),
+            })
+        }
+
+        /// Continue a previously-saved search until its total iteration count (including the
+        /// iterations already recorded in this trace) reaches `config.max_iterations`.
+        pub fn resume<F>(self, config: &NelderMeadConfig, objective: F) -> SimplexTrace
+        where
+            F: FnMut(&[f64]) -> f64,
+        {
+            run_iterations(self.vertices, self.iteration, config, objective)
+        }
+    }
+
+    /// Clamp every weight index to non-negative and renormalize the weight subset of `point` to
+    /// sum to 1, mirroring the config's own non-negative-weight invariant (see
+    /// `Config::validate`). Callers should apply this inside their objective function before
+    /// every evaluation, per the Nelder–Mead spec for this optimizer.
+    pub fn normalize_weights(point: &mut [f64], weight_indices: &[usize]) {
+        for &i in weight_indices {
+            if point[i] < 0.0 {
+                point[i] = 0.0;
+            }
+        }
+        let sum: f64 = weight_indices.iter().map(|&i| point[i]).sum();
+        if sum > 0.0 {
+            for &i in weight_indices {
+                point[i] /= sum;
+            }
+        }
+    }
+
+    /// Run a fresh Nelder–Mead search starting from `initial`, perturbing each axis by
+    /// `step_size` to build the initial simplex.
+    pub fn nelder_mead<F>(
+        initial: Vec<f64>,
+        step_size: f64,
+        config: &NelderMeadConfig,
+        mut objective: F,
+    ) -> SimplexTrace
+    where
+        F: FnMut(&[f64]) -> f64,
+    {
+        let dims = initial.len();
+        let mut vertices = Vec::with_capacity(dims + 1);
+        vertices.push(SimplexVertex {
+            value: objective(&initial),
+            point: initial.clone(),
+        });
+        for d in 0..dims {
+            let mut point = initial.clone();
+            point[d] += step_size;
+            vertices.push(SimplexVertex {
+                value: objective(&point),
+                point,
+            });
+        }
+        run_iterations(vertices, 0, config, objective)
+    }
+
+    fn run_iterations<F>(
+        mut vertices: Vec<SimplexVertex>,
+        start_iteration: usize,
+        config: &NelderMeadConfig,
+        mut objective: F,
+    ) -> SimplexTrace
+    where
+        F: FnMut(&[f64]) -> f64,
+    {
+        let mut iteration = start_iteration;
+        while iteration < config.max_iterations {
+            vertices.sort_by(|a, b| a.value.total_cmp(&b.value));
+            if simplex_diameter(&vertices) < config.tolerance {
+                break;
+            }
+
+            let worst = vertices.len() - 1;
+            let second_worst = vertices.len() - 2;
+            let centroid = centroid_excluding(&vertices, worst);
+
+            let reflected_point = affine(&centroid, &vertices[worst].point, -config.alpha);
+            let reflected = SimplexVertex {
+                value: objective(&reflected_point),
+                point: reflected_point,
+            };
+
+            if reflected.value < vertices[0].value {
+                let expanded_point = affine(&centroid, &reflected.point, config.gamma);
+                let expanded = SimplexVertex {
+                    value: objective(&expanded_point),
+                    point: expanded_point,
+                };
+                vertices[worst] = if expanded.value < reflected.value {
+                    expanded
+                } else {
+                    reflected
+                };
+            } else if reflected.value < vertices[second_worst].value {
+                vertices[worst] = reflected;
+            } else {
+                let contracted_point = affine(&centroid, &vertices[worst].point, config.rho);
+                let contracted = SimplexVertex {
+                    value: objective(&contracted_point),
+                    point: contracted_point,
+                };
+                if contracted.value < vertices[worst].value {
+                    vertices[worst] = contracted;
+                } else {
+                    let best_point = vertices[0].point.clone();
+                    for vertex in vertices.iter_mut().skip(1) {
+                        vertex.point = affine(&best_point, &vertex.point, config.sigma);
+                        vertex.value = objective(&vertex.point);
+                    }
+                }
+            }
+            iteration += 1;
+        }
+
+        vertices.sort_by(|a, b| a.value.total_cmp(&b.value));
+        SimplexTrace {
+            config: config.clone(),
+            iteration,
+            best: vertices[0].clone(),
+            vertices,
+        }
+    }
+
+    /// `base + coeff * (other - base)`, applied component-wise. Reflection, expansion,
+    /// contraction, and shrinkage are all this same affine combination with a different `coeff`
+    /// (and, for reflection, a negated one — see `run_iterations`).
+    fn affine(base: &[f64], other: &[f64], coeff: f64) -> Vec<f64> {
+        base.iter()
+            .zip(other)
+            .map(|(b, o)| b + coeff * (o - b))
+            .collect()
+    }
+
+    fn centroid_excluding(vertices: &[SimplexVertex], exclude: usize) -> Vec<f64> {
+        let dims = vertices[0].point.len();
+        let mut centroid = vec![0.0; dims];
+        for (i, vertex) in vertices.iter().enumerate() {
+            if i == exclude {
+                continue;
+            }
+            for (c, p) in centroid.iter_mut().zip(&vertex.point) {
+                *c += p;
+            }
+        }
+        let n = (vertices.len() - 1) as f64;
+        for c in centroid.iter_mut() {
+            *c /= n;
+        }
+        centroid
+    }
+
+    /// The largest pairwise Euclidean distance between any two vertices; the search stops once
+    /// this falls below `config.tolerance`.
+    fn simplex_diameter(vertices: &[SimplexVertex]) -> f64 {
+        let mut max_distance: f64 = 0.0;
+        for i in 0..vertices.len() {
+            for j in (i + 1)..vertices.len() {
+                let distance: f64 = vertices[i]
+                    .point
+                    .iter()
+                    .zip(&vertices[j].point)
+                    .map(|(a, b)| (a - b).powi(2))
+                    .sum::<f64>()
+                    .sqrt();
+                max_distance = max_distance.max(distance);
+            }
+        }
+        max_distance
+    }
+
+    /// One previously-logged inference to replay when tuning a function's variant weights: which
+    /// variant produced it, and the value of the metric being optimized for that episode.
+    #[derive(Debug, Clone)]
+    pub struct ReplayedInference {
+        pub variant_name: String,
+        pub metric_value: f64,
+    }
+
+    /// Tunes `function_name`'s variant weights in `config` against `metric_name`, using
+    /// `inferences` (previously logged, already-scored episodes) as an offline replay set, writes
+    /// the tuned weights back into `config` in memory, and persists each tuned weight to
+    /// `config_path` on disk via [`super::Config::set`] (format-preserving, and re-validated
+    /// before anything is written — see `set`'s own doc comment), so a tuning run's results
+    /// survive the next config reload instead of only living in this in-memory `Config`.
+    ///
+    /// The objective handed to [`nelder_mead`] is a direct, unweighted estimate: for a candidate
+    /// weight vector, the expected metric is the weighted average of each variant's mean observed
+    /// `metric_value` across `inferences`, oriented by the metric's [`super::MetricConfigOptimize`]
+    /// (Nelder–Mead always minimizes, so a `Max`-optimized metric's expected value is negated). This
+    /// assumes each variant's logged episodes are representative of what it would have produced
+    /// under a different weight — a reasonable offline approximation, but not a full off-policy
+    /// correction (e.g. no inverse-propensity weighting of `inferences` by the weights they were
+    /// actually logged under). A variant with no replayed inferences contributes a mean score of 0,
+    /// so its tuned weight is driven entirely by the other variants' scores.
+    ///
+    /// Assumes the resolved `VariantConfig::ChatCompletion`'s `weight` field is `pub`, as already
+    /// relied on for `model`/`json_mode` elsewhere in this file; `crate::variant` isn't available
+    /// here to confirm a setter exists instead.
+    pub fn tune_variant_weights(
+        config: &mut super::Config,
+        config_path: &str,
+        function_name: &str,
+        metric_name: &str,
+        inferences: &[ReplayedInference],
+        nelder_mead_config: &NelderMeadConfig,
+    ) -> Result<SimplexTrace, Error> {
+        let optimize_sign = match config
+            .metrics
+            .get(metric_name)
+            .ok_or_else(|| Error::Config {
+                message: format!("Invalid Config: unknown metric `{metric_name}` for tuning"),
+            })?
+            .optimize
+        {
+            super::MetricConfigOptimize::Max => 1.0,
+            super::MetricConfigOptimize::Min => -1.0,
+        };
+
+        let function = config
+            .functions
+            .get(function_name)
+            .ok_or_else(|| Error::Config {
+                message: format!("Invalid Config: unknown function `{function_name}` for tuning"),
+            })?;
+        let mut variant_names: Vec<String> = function.variants().keys().cloned().collect();
+        variant_names.sort();
+        if variant_names.is_empty() {
+            return Err(Error::Config {
+                message: format!(
+                    "Invalid Config: function `{function_name}` has no variants to tune"
+                ),
+            });
+        }
+
+        let initial: Vec<f64> = variant_names
+            .iter()
+            .map(|name| function.variants()[name].weight())
+            .collect();
+        let mean_scores: Vec<f64> = variant_names
+            .iter()
+            .map(|name| {
+                let (sum, count) = inferences
+                    .iter()
+                    .filter(|inference| &inference.variant_name == name)
+                    .fold((0.0, 0usize), |(sum, count), inference| {
+                        (sum + inference.metric_value, count + 1)
+                    });
+                if count > 0 {
+                    sum / count as f64
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        let weight_indices: Vec<usize> = (0..variant_names.len()).collect();
+        let objective = {
+            let weight_indices = weight_indices.clone();
+            move |point: &[f64]| {
+                let mut point = point.to_vec();
+                normalize_weights(&mut point, &weight_indices);
+                let expected: f64 = point.iter().zip(&mean_scores).map(|(w, s)| w * s).sum();
+                -optimize_sign * expected
+            }
+        };
+
+        let trace = nelder_mead(initial, 0.1, nelder_mead_config, objective);
+
+        let mut tuned_weights = trace.best.point.clone();
+        normalize_weights(&mut tuned_weights, &weight_indices);
+
+        let function = config
+            .functions
+            .get_mut(function_name)
+            .expect("function_name was already validated to exist above");
+        let variants = match function {
+            super::FunctionConfig::Chat(chat) => &mut chat.variants,
+            super::FunctionConfig::Json(json) => &mut json.variants,
+        };
+        for (name, tuned_weight) in variant_names.iter().zip(&tuned_weights) {
+            if let Some(super::VariantConfig::ChatCompletion(params)) = variants.get_mut(name) {
+                params.weight = *tuned_weight;
+            }
+        }
+
+        for (name, tuned_weight) in variant_names.iter().zip(tuned_weights) {
+            super::Config::set(
+                config_path,
+                &format!("functions.{function_name}.variants.{name}.weight"),
+                tuned_weight,
+            )?;
+        }
+
+        Ok(trace)
+    }
+}
+
+/// Hashing/invalidation/zero-copy-read primitives for a would-be compiled-cache layer over
+/// `tensorzero.toml`, so that repeated gateway boots don't have to re-parse and re-validate it
+/// from scratch.
+///
+/// **Not currently called from [`Config::load()`].** Caching the fully resolved [`Config`] with
+/// `rkyv` would need `rkyv::Archive` / `rkyv::Serialize` / `rkyv::Deserialize` derives on
+/// [`FunctionConfig`], [`ModelConfig`], [`StaticToolConfig`], and [`TemplateConfig`], which live
+/// in `crate::function`, `crate::model`, `crate::tool`, and `crate::minijinja_util` respectively
+/// and don't derive those traits today. Without that, the only thing this module could cache —
+/// [`CachedConfigSummary`], a name-only projection — can't reconstruct a [`Config`], so wiring it
+/// into `load()` would add a stat/hash/write to every boot for zero reduction in parsing work,
+/// which is worse than not caching at all. This module is kept as tested, ready-to-use
+/// infrastructure (see its own unit tests) for whoever adds those derives and wires a real
+/// cache-hit short-circuit into `load()`; it isn't a working cache on its own yet.
+pub mod compiled_cache {
+    use super::*;
+    use std::time::SystemTime;
+
+    /// The subset of a resolved [`Config`] that can be archived with `rkyv` today.
+    #[derive(Debug, Clone, PartialEq, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+    #[archive(check_bytes)]
+    pub struct CachedConfigSummary {
+        pub gateway_bind_address: Option<String>,
+        pub gateway_disable_observability: bool,
+        pub models: Vec<String>,
+        pub functions: Vec<String>,
+        pub tools: Vec<String>,
+        pub template_partials: Vec<String>,
+    }
+
+    impl CachedConfigSummary {
+        pub fn from_config(config: &Config) -> Self {
+            let mut models: Vec<String> = config.models.keys().cloned().collect();
+            models.sort();
+            let mut functions: Vec<String> = config.functions.keys().cloned().collect();
+            functions.sort();
+            let mut tools: Vec<String> = config.tools.keys().cloned().collect();
+            tools.sort();
+            let mut template_partials: Vec<String> =
+                config.template_partials.keys().cloned().collect();
+            template_partials.sort();
+
+            Self {
+                gateway_bind_address: config.gateway.bind_address.map(|addr| addr.to_string()),
+                gateway_disable_observability: config.gateway.disable_observability,
+                models,
+                functions,
+                tools,
+                template_partials,
+            }
+        }
+    }
+
+    /// Hashes `source_toml` together with the mtime of every path in `referenced_files`
+    /// (schema files, templates, partials — anything `load_from_toml` reads off disk besides
+    /// the TOML itself) into a cache key. Any edit to the config text or to a file it points
+    /// at changes the key and invalidates the cache.
+    pub fn compute_cache_key(source_toml: &str, referenced_files: &[PathBuf]) -> Result<String, Error> {
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        source_toml.hash(&mut hasher);
+
+        let mut paths: Vec<&PathBuf> = referenced_files.iter().collect();
+        paths.sort();
+        for path in paths {
+            path.hash(&mut hasher);
+            let mtime = std::fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .map_err(|e| Error::Config {
+                    message: format!("Failed to stat `{}` for cache key: {e}", path.display()),
+                })?;
+            mtime
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+                .hash(&mut hasher);
+        }
+
+        Ok(format!("{:x}", hasher.finish()))
+    }
+
+    /// Serializes `summary` with `rkyv` and writes it to `cache_path`, prefixed with
+    /// `cache_key` so [`load`] can check it without deserializing the archive first.
+    pub fn save(
+        cache_path: &Path,
+        cache_key: &str,
+        summary: &CachedConfigSummary,
+    ) -> Result<(), Error> {
+        let bytes = rkyv::to_bytes::<_, 256>(summary).map_err(|e| Error::Config {
+            message: format!("Failed to serialize compiled config cache: {e}"),
+        })?;
+
+        let mut file_bytes = Vec::with_capacity(cache_key.len() + 1 + bytes.len());
+        file_bytes.extend_from_slice(cache_key.as_bytes());
+        file_bytes.push(b'\n');
+        file_bytes.extend_from_slice(&bytes);
+
+        std::fs::write(cache_path, file_bytes).map_err(|e| Error::Config {
+            message: format!(
+                "Failed to write compiled config cache to `{}`: {e}",
+                cache_path.display()
+            ),
+        })
+    }
+
+    /// Validates (with `rkyv`'s `CheckBytes`) and deserializes the cache at `cache_path`, but
+    /// only if its stored key matches `expected_cache_key`. Returns `None`
+    /// on any key mismatch, missing file, or validation failure, so callers transparently
+    /// fall back to a full `Config::load_from_toml` rather than ever serving a stale or
+    /// corrupt cache.
+    pub fn load(cache_path: &Path, expected_cache_key: &str) -> Option<CachedConfigSummary> {
+        let contents = std::fs::read(cache_path).ok()?;
+        let newline = contents.iter().position(|&b| b == b'\n')?;
+        let (stored_key, rest) = contents.split_at(newline);
+        if stored_key != expected_cache_key.as_bytes() {
+            return None;
+        }
+
+        // The archive starts right after the variable-length key, so copy it into an aligned
+        // buffer before validation, which rejects unaligned archives.
+        let mut bytes = rkyv::AlignedVec::with_capacity(rest.len() - 1);
+        bytes.extend_from_slice(&rest[1..]);
+        let archived = rkyv::check_archived_root::<CachedConfigSummary>(&bytes).ok()?;
+        rkyv::Deserialize::<CachedConfigSummary, _>::deserialize(archived, &mut rkyv::Infallible)
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Ensure that the sample valid config can be parsed without panicking
+    #[test]
+    fn test_config_from_toml_table_valid() {
+        let config = get_sample_valid_config();
+        let base_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        Config::load_from_toml(config, base_path.clone()).expect("Failed to load config");
+
+        // Ensure that removing the `[metrics]` section still parses the config
+        let mut config = get_sample_valid_config();
+        config
+            .remove("metrics")
+            .expect("Failed to remove `[metrics]` section");
+        let config = Config::load_from_toml(config, base_path).expect("Failed to load config");
+
+        // Check that the JSON mode is set properly on the JSON variants
+        let prompt_a_json_mode = match config
+            .functions
+            .get("json_with_schemas")
+            .unwrap()
+            .variants()
+            .get("openai_promptA")
+            .unwrap()
+        {
+            VariantConfig::ChatCompletion(chat_config) => &chat_config.json_mode,
+        };
+        assert_eq!(prompt_a_json_mode, &JsonMode::ImplicitTool);
+
+        let prompt_b_json_mode = match config
+            .functions
+            .get("json_with_schemas")
+            .unwrap()
+            .variants()
+            .get("openai_promptB")
+            .unwrap()
+        {
+            VariantConfig::ChatCompletion(chat_config) => &chat_config.json_mode,
+        };
+        assert_eq!(prompt_b_json_mode, &JsonMode::On);
+        // Check that the tool choice for weather_helper is set to "specific" and the correct tool
+        let function = config.functions.get("weather_helper").unwrap();
+        match function {
+            FunctionConfig::Chat(chat_config) => {
+                assert_eq!(
+                    chat_config.tool_choice,
+                    ToolChoice::Specific("get_temperature".to_string())
+                );
+            }
+            _ => panic!("Expected a chat function"),
+        }
+    }
+
+    /// Ensure that the config parsing correctly handles the `gateway.bind_address` field
+    #[test]
+    fn test_config_gateway_bind_address() {
+        let mut config = get_sample_valid_config();
+        let base_path = PathBuf::new();
+
+        // Test with a valid bind address
+        let parsed_config = Config::load_from_toml(config.clone(), base_path.clone()).unwrap();
+        assert_eq!(
+            parsed_config.gateway.bind_address.unwrap().to_string(),
+            "0.0.0.0:3000"
+        );
+
+        // Test with missing gateway section
+        config.remove("gateway");
+        let parsed_config = Config::load_from_toml(config.clone(), base_path.clone()).unwrap();
+        assert!(parsed_config.gateway.bind_address.is_none());
+
+        // Test with missing bind_address
+        config.insert(
+            "gateway".to_string(),
+            toml::Value::Table(toml::Table::new()),
+        );
+        let parsed_config = Config::load_from_toml(config.clone(), base_path.clone()).unwrap();
+        assert!(parsed_config.gateway.bind_address.is_none());
+
+        // Test with invalid bind address
+        config["gateway"].as_table_mut().unwrap().insert(
+            "bind_address".to_string(),
+            toml::Value::String("invalid_address".to_string()),
+        );
+        let result = Config::load_from_toml(config, base_path);
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Config {
+                message: "Failed to parse config:\ninvalid socket address syntax\nin `gateway.bind_address`\n".to_string()
+            }
+        );
+    }
+
+    /// A minimal valid config table (no file-backed schemas, so it doesn't depend on fixtures)
+    /// for exercising `include` resolution relative to an arbitrary `base_path`.
+    fn minimal_config_table() -> toml::Table {
+        let config_str = r#"
+        [models."test-model"]
+        routing = ["only"]
+
+        [models."test-model".providers.only]
+        type = "openai"
+        model_name = "gpt"
+
+        [functions.f]
+        type = "chat"
+
+        [functions.f.variants.v]
+        type = "chat_completion"
+        weight = 1.0
+        model = "test-model"
+        "#;
+        toml::from_str(config_str).expect("Failed to parse minimal config")
+    }
+
+    /// Ensure that `include = [...]` merges matching files in, and that two included files
+    /// defining the same fully-qualified key are rejected rather than silently last-wins
+    #[test]
+    fn test_config_include_merges_files_and_detects_conflicts() {
+        let dir =
+            std::env::temp_dir().join(format!("tensorzero_test_include_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("Failed to create temp include dir");
+
+        std::fs::write(
+            dir.join("a.toml"),
+            r#"
+            [metrics.from_a]
+            type = "boolean"
+            optimize = "max"
+            level = "inference"
+            "#,
+        )
+        .expect("Failed to write a.toml");
+
+        let mut config = minimal_config_table();
+        config.insert(
+            "include".to_string(),
+            toml::Value::Array(vec![toml::Value::String("a.toml".to_string())]),
+        );
+        let loaded = Config::load_from_toml(config, dir.clone())
+            .expect("Failed to load config with include");
+        assert!(loaded.metrics.contains_key("from_a"));
+
+        // A second included file that redefines the same metric is a conflict, not last-wins.
+        std::fs::write(
+            dir.join("b.toml"),
+            r#"
+            [metrics.from_a]
+            type = "boolean"
+            optimize = "min"
+            level = "episode"
+            "#,
+        )
+        .expect("Failed to write b.toml");
+
+        let mut config = minimal_config_table();
+        config.insert(
+            "include".to_string(),
+            toml::Value::Array(vec![
+                toml::Value::String("a.toml".to_string()),
+                toml::Value::String("b.toml".to_string()),
+            ]),
+        );
+        let result = Config::load_from_toml(config, dir.clone());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("is defined by both"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    /// A schema path set inside an included file must resolve relative to *that file's*
+    /// directory, not the root config's `base_path` — even though both tables end up merged
+    /// into the same root `Config`.
+    #[test]
+    fn test_config_include_rebases_schema_paths_relative_to_included_file() {
+        let dir = std::env::temp_dir().join(format!(
+            "tensorzero_test_include_schema_rebase_{}",
+            std::process::id()
+        ));
+        let tools_dir = dir.join("tools");
+        std::fs::create_dir_all(&tools_dir).expect("Failed to create temp include dir");
+
+        std::fs::write(
+            tools_dir.join("parameters.json"),
+            r#"{"type": "object", "properties": {}}"#,
+        )
+        .expect("Failed to write parameters.json");
+
+        // `parameters` is relative to `tools/`, where this file lives, not to `dir`.
+        std::fs::write(
+            tools_dir.join("tools.toml"),
+            r#"
+            [tools.included_tool]
+            description = "A tool defined in an included file"
+            parameters = "parameters.json"
+            "#,
+        )
+        .expect("Failed to write tools.toml");
+
+        let mut config = minimal_config_table();
+        config.insert(
+            "include".to_string(),
+            toml::Value::Array(vec![toml::Value::String("tools/tools.toml".to_string())]),
+        );
+        let loaded = Config::load_from_toml(config, dir.clone())
+            .expect("Failed to load config with an included tool's schema path");
+        assert!(loaded.tools.contains_key("included_tool"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    /// A key set purely by an `include`d file (never present in the root table) should be
+    /// reported by `describe_value` as having come from that file.
+    #[test]
+    fn test_config_include_populates_sources() {
+        let dir = std::env::temp_dir().join(format!(
+            "tensorzero_test_include_sources_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).expect("Failed to create temp include dir");
+
+        let included_path = dir.join("a.toml");
+        std::fs::write(
+            &included_path,
+            r#"
+            [metrics.from_a]
+            type = "boolean"
+            optimize = "max"
+            level = "inference"
+            "#,
+        )
+        .expect("Failed to write a.toml");
+
+        let mut config = minimal_config_table();
+        config.insert(
+            "include".to_string(),
+            toml::Value::Array(vec![toml::Value::String("a.toml".to_string())]),
+        );
+        let loaded = Config::load_from_toml(config, dir.clone())
+            .expect("Failed to load config with include");
+
+        assert_eq!(
+            loaded.describe_value("metrics.from_a.type"),
+            Some(&ConfigSource::Include(included_path))
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    /// `Config::update_configuration` should edit the requested key in place while preserving
+    /// comments and formatting elsewhere in the file, and should reject an edit that would make
+    /// the config invalid (here, an empty `routing`) without touching the file on disk.
+    #[test]
+    fn test_config_update_configuration() {
+        let path = std::env::temp_dir().join(format!(
+            "tensorzero_test_update_configuration_{}.toml",
+            std::process::id()
+        ));
+        let original = r#"
+        # A comment that must survive the edit.
+        [models."test-model"]
+        routing = ["only"]
+
+        [models."test-model".providers.only]
+        type = "openai"
+        model_name = "gpt"
+
+        [functions.f]
+        type = "chat"
+
+        [functions.f.variants.v]
+        type = "chat_completion"
+        weight = 1.0
+        model = "test-model"
+        "#;
+        std::fs::write(&path, original).expect("Failed to write test config");
+
+        Config::update_configuration(
+            path.to_str().unwrap(),
+            "functions.f.variants.v.weight",
+            toml_edit::Value::from(2.5),
+        )
+        .expect("Failed to update configuration");
+
+        let updated = std::fs::read_to_string(&path).expect("Failed to read updated config");
+        assert!(updated.contains("A comment that must survive the edit."));
+        assert!(updated.contains("weight = 2.5"));
+
+        let result = Config::update_configuration(
+            path.to_str().unwrap(),
+            "models.test-model.routing",
+            toml_edit::Value::from(toml_edit::Array::new()),
+        );
+        assert!(result.is_err());
+        let unchanged = std::fs::read_to_string(&path).expect("Failed to read config after rejected edit");
+        assert_eq!(updated, unchanged);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    /// `Config::set` should accept plain Rust values (here, a `&str`) rather than requiring
+    /// callers to build a `toml_edit::Value` by hand.
+    #[test]
+    fn test_config_set_accepts_plain_values() {
+        let path = std::env::temp_dir().join(format!(
+            "tensorzero_test_config_set_{}.toml",
+            std::process::id()
+        ));
+        std::fs::write(
+            &path,
+            r#"
+        [models."test-model"]
+        routing = ["only"]
+
+        [models."test-model".providers.only]
+        type = "openai"
+        model_name = "gpt"
+
+        [models."other-model"]
+        routing = ["only"]
+
+        [models."other-model".providers.only]
+        type = "openai"
+        model_name = "gpt-4o"
+
+        [functions.f]
+        type = "chat"
+
+        [functions.f.variants.v]
+        type = "chat_completion"
+        weight = 1.0
+        model = "test-model"
+        "#,
+        )
+        .expect("Failed to write test config");
+
+        Config::set(path.to_str().unwrap(), "functions.f.variants.v.model", "other-model")
+            .expect("Failed to set `model` via Config::set");
+
+        let updated = std::fs::read_to_string(&path).expect("Failed to read updated config");
+        assert!(updated.contains(r#"model = "other-model""#));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    /// A valid `[template_engine]` section should parse and be preserved on the loaded `Config`.
+    #[test]
+    fn test_config_template_engine_valid() {
+        let mut config = minimal_config_table();
+        let mut template_engine = toml::Table::new();
+        template_engine.insert("block_start".to_string(), "{%".into());
+        template_engine.insert("block_end".to_string(), "%}".into());
+        template_engine.insert("whitespace_mode".to_string(), "trim_blocks".into());
+        config.insert(
+            "template_engine".to_string(),
+            toml::Value::Table(template_engine),
+        );
+
+        let loaded = Config::load_from_toml(config, PathBuf::new())
+            .expect("Failed to load config with `[template_engine]`");
+        let template_engine = loaded
+            .template_engine
+            .expect("`template_engine` should be Some");
+        assert_eq!(template_engine.block_start.as_deref(), Some("{%"));
+        assert_eq!(template_engine.whitespace_mode, WhitespaceMode::TrimBlocks);
+    }
+
+    /// Specifying only one half of a delimiter pair is rejected.
+    #[test]
+    fn test_config_template_engine_unpaired_delimiter() {
+        let mut config = minimal_config_table();
+        let mut template_engine = toml::Table::new();
+        template_engine.insert("block_start".to_string(), "{%".into());
+        config.insert(
+            "template_engine".to_string(),
+            toml::Value::Table(template_engine),
+        );
+
+        let result = Config::load_from_toml(config, PathBuf::new());
+        assert!(result.unwrap_err().to_string().contains(
+            "`template_engine.block_start`/`block_end` must both be specified, or neither"
+        ));
+    }
+
+    /// Reusing the same delimiter string for two different roles is rejected.
+    #[test]
+    fn test_config_template_engine_duplicate_delimiter() {
+        let mut config = minimal_config_table();
+        let mut template_engine = toml::Table::new();
+        template_engine.insert("block_start".to_string(), "<%".into());
+        template_engine.insert("block_end".to_string(), "%>".into());
+        template_engine.insert("variable_start".to_string(), "<%".into());
+        template_engine.insert("variable_end".to_string(), "%>>".into());
+        config.insert(
+            "template_engine".to_string(),
+            toml::Value::Table(template_engine),
+        );
+
+        let result = Config::load_from_toml(config, PathBuf::new());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("delimiter `<%` is used more than once"));
+    }
+
+    /// A variant template's `{% include %}` of a registered `[template_partials]` entry should
+    /// resolve successfully at config-load time.
+    #[test]
+    fn test_config_template_partials_resolve() {
+        let dir = std::env::temp_dir().join(format!(
+            "tensorzero_test_template_partials_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(dir.join("partials")).expect("Failed to create temp dir");
+        std::fs::write(
+            dir.join("system_template.minijinja"),
+            r#"{% include "shared/tool_preamble" %}"#,
+        )
+        .expect("Failed to write system_template.minijinja");
+        std::fs::write(
+            dir.join("partials").join("tool_preamble.minijinja"),
+            "Use the available tools when helpful.",
+        )
+        .expect("Failed to write partial");
+
+        let mut config = minimal_config_table();
+        config["functions"]["f"]["variants"]["v"]
+            .as_table_mut()
+            .expect("Failed to get `functions.f.variants.v` section")
+            .insert(
+                "system_template".to_string(),
+                "system_template.minijinja".into(),
+            );
+        let mut system_schema = toml::Table::new();
+        system_schema.insert("type".to_string(), "object".into());
+        config["functions"]["f"]
+            .as_table_mut()
+            .expect("Failed to get `functions.f` section")
+            .insert("system_schema".to_string(), toml::Value::Table(system_schema));
+        let mut template_partials = toml::Table::new();
+        template_partials.insert(
+            "shared/tool_preamble".to_string(),
+            "partials/tool_preamble.minijinja".into(),
+        );
+        config.insert(
+            "template_partials".to_string(),
+            toml::Value::Table(template_partials),
+        );
+
+        Config::load_from_toml(config, dir.clone())
+            .expect("Failed to load config with a resolved template partial");
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    /// A variant template that includes a partial name missing from `[template_partials]` is
+    /// rejected at config-load time, naming the referencing template and the missing partial.
+    #[test]
+    fn test_config_template_partials_missing_partial() {
+        let dir = std::env::temp_dir().join(format!(
+            "tensorzero_test_template_partials_missing_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).expect("Failed to create temp dir");
+        std::fs::write(
+            dir.join("system_template.minijinja"),
+            r#"{% include "shared/tool_preamble" %}"#,
+        )
+        .expect("Failed to write system_template.minijinja");
+
+        let mut config = minimal_config_table();
+        config["functions"]["f"]["variants"]["v"]
+            .as_table_mut()
+            .expect("Failed to get `functions.f.variants.v` section")
+            .insert(
+                "system_template".to_string(),
+                "system_template.minijinja".into(),
+            );
+        let mut system_schema = toml::Table::new();
+        system_schema.insert("type".to_string(), "object".into());
+        config["functions"]["f"]
+            .as_table_mut()
+            .expect("Failed to get `functions.f` section")
+            .insert("system_schema".to_string(), toml::Value::Table(system_schema));
+
+        let result = Config::load_from_toml(config, dir.clone());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("references unknown partial `shared/tool_preamble`"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    /// Nelder–Mead should converge on the minimum of a simple, known-convex objective.
+    #[test]
+    fn test_nelder_mead_converges_on_sphere() {
+        let objective = |point: &[f64]| (point[0] - 3.0).powi(2) + (point[1] + 2.0).powi(2);
+        let trace = tuning::nelder_mead(
+            vec![0.0, 0.0],
+            1.0,
+            &tuning::NelderMeadConfig::default(),
+            objective,
+        );
+        assert!(trace.best.value < 1e-4, "best value was {}", trace.best.value);
+        assert!((trace.best.point[0] - 3.0).abs() < 0.05);
+        assert!((trace.best.point[1] + 2.0).abs() < 0.05);
+    }
+
+    /// `resume` should be able to pick a search back up from a saved trace and keep improving.
+    #[test]
+    fn test_nelder_mead_resume_from_saved_trace() {
+        let objective = |point: &[f64]| (point[0] - 3.0).powi(2) + (point[1] + 2.0).powi(2);
+        let short_config = tuning::NelderMeadConfig {
+            max_iterations: 3,
+            ..tuning::NelderMeadConfig::default()
+        };
+        let partial = tuning::nelder_mead(vec![0.0, 0.0], 1.0, &short_config, objective);
+
+        let path = std::env::temp_dir().join(format!(
+            "tensorzero_test_tuning_trace_{}.json",
+            std::process::id()
+        ));
+        partial.save(&path).expect("Failed to save tuning trace");
+        let loaded = tuning::SimplexTrace::load(&path).expect("Failed to load tuning trace");
+        std::fs::remove_file(&path).ok();
+
+        let full_config = tuning::NelderMeadConfig::default();
+        let resumed = loaded.resume(&full_config, objective);
+        assert!(resumed.best.value <= partial.best.value);
+        assert!(resumed.best.value < 1e-4);
+    }
 
-#[cfg(test)]
-mod tests {
+    /// `normalize_weights` should clamp negative weights to zero and renormalize the weight
+    /// subset of the parameter vector to sum to 1, leaving other indices untouched.
+    #[test]
+    fn test_normalize_weights() {
+        let mut point = vec![-1.0, 3.0, 0.5];
+        tuning::normalize_weights(&mut point, &[0, 1]);
+        assert_eq!(point[0], 0.0);
+        assert_eq!(point[1], 1.0);
+        assert_eq!(point[2], 0.5);
+    }
 
-    use crate::variant::JsonMode;
+    /// `tune_variant_weights` should shift `generate_draft`'s variant weights toward whichever
+    /// variant's replayed inferences scored better on a `max`-optimized metric.
+    #[test]
+    fn test_tune_variant_weights_favors_higher_scoring_variant() {
+        let base_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let mut config = Config::load_from_toml(get_sample_valid_config(), base_path)
+            .expect("Failed to load sample config");
+
+        // Written next to the sample config's `fixtures/` paths, since `Config::set` re-validates
+        // the edited file relative to its own directory.
+        let config_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(format!(
+            "tensorzero_test_tune_variant_weights_{}.toml",
+            std::process::id()
+        ));
+        std::fs::write(&config_path, get_sample_valid_config_str())
+            .expect("Failed to write sample config to disk");
+        let config_path = config_path.to_str().expect("config_path is not valid UTF-8");
+
+        let inferences = vec![
+            tuning::ReplayedInference {
+                variant_name: "openai_promptA".to_string(),
+                metric_value: 0.0,
+            },
+            tuning::ReplayedInference {
+                variant_name: "openai_promptA".to_string(),
+                metric_value: 0.0,
+            },
+            tuning::ReplayedInference {
+                variant_name: "openai_promptB".to_string(),
+                metric_value: 1.0,
+            },
+            tuning::ReplayedInference {
+                variant_name: "openai_promptB".to_string(),
+                metric_value: 1.0,
+            },
+        ];
+
+        tuning::tune_variant_weights(
+            &mut config,
+            config_path,
+            "generate_draft",
+            "task_success",
+            &inferences,
+            &tuning::NelderMeadConfig::default(),
+        )
+        .expect("Failed to tune variant weights");
+
+        let variants = config.functions["generate_draft"].variants();
+        let weight_a = variants["openai_promptA"].weight();
+        let weight_b = variants["openai_promptB"].weight();
+        assert!(
+            weight_b > weight_a,
+            "expected openai_promptB (higher-scoring) to end up with more weight than \
+             openai_promptA, got {weight_a} vs {weight_b}"
+        );
+        assert!((weight_a + weight_b - 1.0).abs() < 1e-6);
 
-    use super::*;
+        let persisted = std::fs::read_to_string(config_path)
+            .expect("Failed to read back persisted config")
+            .parse::<toml::Table>()
+            .expect("Failed to parse persisted config");
+        let persisted_weight_a = persisted["functions"]["generate_draft"]["variants"]
+            ["openai_promptA"]["weight"]
+            .as_float()
+            .expect("persisted weight is not a float");
+        let persisted_weight_b = persisted["functions"]["generate_draft"]["variants"]
+            ["openai_promptB"]["weight"]
+            .as_float()
+            .expect("persisted weight is not a float");
+        assert!((persisted_weight_a - weight_a).abs() < 1e-6);
+        assert!((persisted_weight_b - weight_b).abs() < 1e-6);
+
+        std::fs::remove_file(config_path).expect("Failed to remove temp config file");
+    }
 
-    /// Ensure that the sample valid config can be parsed without panicking
+    /// `tune_variant_weights` should reject an unknown metric or function name rather than
+    /// silently tuning against nothing.
     #[test]
-    fn test_config_from_toml_table_valid() {
-        let config = get_sample_valid_config();
+    fn test_tune_variant_weights_rejects_unknown_metric_or_function() {
         let base_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        Config::load_from_toml(config, base_path.clone()).expect("Failed to load config");
-
-        // Ensure that removing the `[metrics]` section still parses the config
-        let mut config = get_sample_valid_config();
-        config
-            .remove("metrics")
-            .expect("Failed to remove `[metrics]` section");
-        let config = Config::load_from_toml(config, base_path).expect("Failed to load config");
+        let mut config = Config::load_from_toml(get_sample_valid_config(), base_path)
+            .expect("Failed to load sample config");
+        // Both calls below are rejected before `tune_variant_weights` ever resolves/writes
+        // `config_path`, so a path to a config file that doesn't exist is fine here.
+        let config_path = "/nonexistent/tensorzero.toml";
+
+        let err = tuning::tune_variant_weights(
+            &mut config,
+            config_path,
+            "generate_draft",
+            "nonexistent_metric",
+            &[],
+            &tuning::NelderMeadConfig::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::Config { message } if message.contains("unknown metric")));
+
+        let err = tuning::tune_variant_weights(
+            &mut config,
+            config_path,
+            "nonexistent_function",
+            "task_success",
+            &[],
+            &tuning::NelderMeadConfig::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::Config { message } if message.contains("unknown function")));
+    }
 
-        // Check that the JSON mode is set properly on the JSON variants
-        let prompt_a_json_mode = match config
-            .functions
-            .get("json_with_schemas")
-            .unwrap()
-            .variants()
-            .get("openai_promptA")
+    /// `Config::dump` should surface the locally-defined sections (`gateway`, `metrics`) in full,
+    /// `models` as a sorted name list (`ModelConfig` isn't `Serialize` yet), and `functions`/
+    /// `tools` expanded into their resolved [`FunctionConfigDump`]/[`StaticToolConfigDump`] shapes.
+    #[test]
+    fn test_config_dump() {
+        let base_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let config = Config::load_from_toml(get_sample_valid_config(), base_path)
+            .expect("Failed to load sample config");
+        let dump = config.dump().expect("Failed to dump config");
+
+        assert_eq!(dump["gateway"]["disable_observability"], false);
+        assert!(dump["metrics"].is_object());
+        assert!(dump["models"].is_array());
+        assert!(dump["functions"].is_object());
+        assert!(dump["tools"].is_object());
+
+        let mut expected_models: Vec<&str> = config.models.keys().map(String::as_str).collect();
+        expected_models.sort_unstable();
+        let actual_models: Vec<&str> = dump["models"]
+            .as_array()
             .unwrap()
-        {
-            VariantConfig::ChatCompletion(chat_config) => &chat_config.json_mode,
-        };
-        assert_eq!(prompt_a_json_mode, &JsonMode::ImplicitTool);
+            .iter()
+            .map(|v| v.as_str().unwrap())
+            .collect();
+        assert_eq!(actual_models, expected_models);
 
-        let prompt_b_json_mode = match config
-            .functions
-            .get("json_with_schemas")
-            .unwrap()
-            .variants()
-            .get("openai_promptB")
-            .unwrap()
-        {
-            VariantConfig::ChatCompletion(chat_config) => &chat_config.json_mode,
-        };
-        assert_eq!(prompt_b_json_mode, &JsonMode::On);
-        // Check that the tool choice for get_weather is set to "specific" and the correct tool
-        let function = config.functions.get("weather_helper").unwrap();
-        match function {
-            FunctionConfig::Chat(chat_config) => {
-                assert_eq!(
-                    chat_config.tool_choice,
-                    ToolChoice::Specific("get_temperature".to_string())
-                );
+        assert_eq!(dump["functions"]["generate_draft"]["type"], "chat");
+        assert_eq!(
+            dump["functions"]["generate_draft"]["variants"]["openai_promptA"]["weight"],
+            0.9
+        );
+        assert_eq!(
+            dump["functions"]["json_with_schemas"]["implicit_tool_call_config"]["tools_available"]
+                [0]["type"],
+            "implicit"
+        );
+        assert_eq!(dump["tools"]["get_temperature"]["name"], "get_temperature");
+        assert_eq!(
+            dump["functions"]["weather_helper"]["tools"][0]["name"],
+            "get_temperature"
+        );
+    }
+
+    /// Ensure that `merge_table` records which source last set a given dotted config path
+    #[test]
+    fn test_config_source_tracking() {
+        let mut sources = HashMap::new();
+        let mut base = toml::Table::new();
+
+        let mut incoming = toml::Table::new();
+        let mut gateway = toml::Table::new();
+        gateway.insert(
+            "bind_address".to_string(),
+            toml::Value::String("0.0.0.0:3000".to_string()),
+        );
+        incoming.insert("gateway".to_string(), toml::Value::Table(gateway));
+
+        let source = ConfigSource::Include(PathBuf::from("extra.toml"));
+        UninitializedConfig::merge_table(&mut base, incoming, &source, "", &mut sources);
+
+        assert_eq!(sources.get("gateway.bind_address"), Some(&source));
+        assert_eq!(
+            base["gateway"]["bind_address"].as_str(),
+            Some("0.0.0.0:3000")
+        );
+    }
+
+    /// Ensure that `${VAR}` / `${VAR:-default}` placeholders are resolved against the
+    /// environment, and that a missing variable with no default is a clear `Error::Config`
+    #[test]
+    fn test_config_env_var_interpolation() {
+        std::env::set_var("TENSORZERO_TEST_AZURE_ENDPOINT", "https://resolved.example.com");
+
+        let mut config = get_sample_valid_config();
+        config["models"]["gpt-3.5-turbo"]["providers"]["azure"]["endpoint"] =
+            "${TENSORZERO_TEST_AZURE_ENDPOINT}".into();
+        config["models"]["gpt-3.5-turbo"]["providers"]["azure"]["deployment_id"] =
+            "${TENSORZERO_TEST_UNSET_DEPLOYMENT_ID:-gpt-35-turbo}".into();
+        let base_path = PathBuf::new();
+        Config::load_from_toml(config, base_path).expect("Failed to load config");
+
+        std::env::remove_var("TENSORZERO_TEST_AZURE_ENDPOINT");
+        let mut config = get_sample_valid_config();
+        config["models"]["gpt-3.5-turbo"]["providers"]["azure"]["endpoint"] =
+            "${TENSORZERO_TEST_AZURE_ENDPOINT}".into();
+        let base_path = PathBuf::new();
+        let result = Config::load_from_toml(config, base_path);
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Config {
+                message: "Failed to resolve config:\nenvironment variable `TENSORZERO_TEST_AZURE_ENDPOINT` is not set\nin `models.gpt-3.5-turbo.providers.azure.endpoint`\n".to_string()
             }
-            _ => panic!("Expected a chat function"),
-        }
+        );
     }
 
-    /// Ensure that the config parsing correctly handles the `gateway.bind_address` field
+    /// Ensure that the explicit `${env:VAR}` / `${env:VAR:-default}` forms resolve the same way
+    /// as the bare `${VAR}` / `${VAR:-default}` forms.
     #[test]
-    fn test_config_gateway_bind_address() {
+    fn test_config_env_var_interpolation_explicit_env_prefix() {
+        std::env::set_var(
+            "TENSORZERO_TEST_AZURE_ENDPOINT_EXPLICIT",
+            "https://resolved-explicit.example.com",
+        );
+
         let mut config = get_sample_valid_config();
+        config["models"]["gpt-3.5-turbo"]["providers"]["azure"]["endpoint"] =
+            "${env:TENSORZERO_TEST_AZURE_ENDPOINT_EXPLICIT}".into();
+        config["models"]["gpt-3.5-turbo"]["providers"]["azure"]["deployment_id"] =
+            "${env:TENSORZERO_TEST_UNSET_DEPLOYMENT_ID:-gpt-35-turbo}".into();
         let base_path = PathBuf::new();
+        Config::load_from_toml(config, base_path).expect("Failed to load config");
+
+        std::env::remove_var("TENSORZERO_TEST_AZURE_ENDPOINT_EXPLICIT");
+    }
+
+    /// Ensure `load_layered_table` applies `TENSORZERO_A__B__C` environment overrides (recording
+    /// `ConfigSource::Env` against the path they set), and that `$TENSORZERO_USER_CONFIG` itself
+    /// never leaks into the merged table as a stray `user_config` key — with
+    /// `#[serde(deny_unknown_fields)]` on `UninitializedConfig`, that would break every load that
+    /// sets the variable.
+    #[test]
+    fn test_load_layered_table_env_override_and_user_config_env_var_excluded() {
+        let dir = std::env::temp_dir().join(format!(
+            "tensorzero_test_layered_table_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let primary_path = dir.join("tensorzero.toml");
+        std::fs::write(&primary_path, "[gateway]\n").unwrap();
+
+        let user_config_path = dir.join("user.toml");
+        std::fs::write(&user_config_path, "[gateway]\n").unwrap();
+
+        std::env::set_var("TENSORZERO_USER_CONFIG", user_config_path.to_str().unwrap());
+        std::env::set_var("TENSORZERO_GATEWAY__BIND_ADDRESS", "0.0.0.0:9000");
+
+        let (table, sources) = UninitializedConfig::load_layered_table(
+            primary_path.to_str().unwrap(),
+            &dir,
+            ConfigSource::Base,
+        )
+        .expect("Failed to build layered config table");
 
-        // Test with a valid bind address
-        let parsed_config = Config::load_from_toml(config.clone(), base_path.clone()).unwrap();
         assert_eq!(
-            parsed_config.gateway.bind_address.unwrap().to_string(),
-            "0.0.0.0:3000"
+            table["gateway"]["bind_address"].as_str(),
+            Some("0.0.0.0:9000")
         );
+        assert_eq!(sources.get("gateway.bind_address"), Some(&ConfigSource::Env));
+        assert!(!table.contains_key("user_config"));
 
-        // Test with missing gateway section
-        config.remove("gateway");
-        let parsed_config = Config::load_from_toml(config.clone(), base_path.clone()).unwrap();
-        assert!(parsed_config.gateway.bind_address.is_none());
+        std::env::remove_var("TENSORZERO_USER_CONFIG");
+        std::env::remove_var("TENSORZERO_GATEWAY__BIND_ADDRESS");
+        std::fs::remove_dir_all(&dir).ok();
+    }
 
-        // Test with missing bind_address
-        config.insert(
-            "gateway".to_string(),
-            toml::Value::Table(toml::Table::new()),
+    /// `convert_yaml_value`/`convert_json_value` reject `null`, since TOML has no null type.
+    #[test]
+    fn test_convert_value_rejects_null() {
+        let yaml_err = convert_yaml_value("config.yaml", serde_yaml::Value::Null).unwrap_err();
+        assert!(matches!(yaml_err, Error::Config { message } if message.contains("TOML has no null type")));
+
+        let json_err = convert_json_value("config.json", serde_json::Value::Null).unwrap_err();
+        assert!(matches!(json_err, Error::Config { message } if message.contains("TOML has no null type")));
+    }
+
+    /// `convert_yaml_value` rejects mapping keys that aren't strings, since TOML table keys must
+    /// be strings (JSON object keys are always strings, so `convert_json_value` has no analogous
+    /// case to test).
+    #[test]
+    fn test_convert_yaml_value_rejects_non_string_keys() {
+        let mut map = serde_yaml::Mapping::new();
+        map.insert(
+            serde_yaml::Value::Number(1.into()),
+            serde_yaml::Value::String("value".to_string()),
         );
-        let parsed_config = Config::load_from_toml(config.clone(), base_path.clone()).unwrap();
-        assert!(parsed_config.gateway.bind_address.is_none());
+        let err = convert_yaml_value("config.yaml", serde_yaml::Value::Mapping(map)).unwrap_err();
+        assert!(matches!(err, Error::Config { message } if message.contains("mapping keys must be strings")));
+    }
 
-        // Test with invalid bind address
-        config["gateway"].as_table_mut().unwrap().insert(
-            "bind_address".to_string(),
-            toml::Value::String("invalid_address".to_string()),
+    /// `convert_yaml_value` unwraps a YAML `!Tag`'d value and converts the value underneath,
+    /// discarding the tag (TOML has no equivalent concept).
+    #[test]
+    fn test_convert_yaml_value_unwraps_tagged() {
+        let tagged = serde_yaml::Value::Tagged(Box::new(serde_yaml::value::TaggedValue {
+            tag: serde_yaml::value::Tag::new("!SomeTag"),
+            value: serde_yaml::Value::String("untagged".to_string()),
+        }));
+        let converted = convert_yaml_value("config.yaml", tagged).unwrap();
+        assert_eq!(converted, toml::Value::String("untagged".to_string()));
+    }
+
+    /// `convert_yaml_value`/`convert_json_value` recursively convert nested sequences/mappings
+    /// (arrays/objects) into the equivalent `toml::Value`.
+    #[test]
+    fn test_convert_value_handles_nested_collections() {
+        let yaml_str = "a:\n  - 1\n  - true\n  - inner: \"x\"\n";
+        let yaml_value: serde_yaml::Value = serde_yaml::from_str(yaml_str).unwrap();
+        let converted = convert_yaml_value("config.yaml", yaml_value).unwrap();
+        let table = converted.as_table().unwrap();
+        let array = table["a"].as_array().unwrap();
+        assert_eq!(array[0], toml::Value::Integer(1));
+        assert_eq!(array[1], toml::Value::Boolean(true));
+        assert_eq!(
+            array[2].as_table().unwrap()["inner"],
+            toml::Value::String("x".to_string())
         );
-        let result = Config::load_from_toml(config, base_path);
+
+        let json_value: serde_json::Value =
+            serde_json::from_str(r#"{"a": [1, true, {"inner": "x"}]}"#).unwrap();
+        let converted = convert_json_value("config.json", json_value).unwrap();
+        let table = converted.as_table().unwrap();
+        let array = table["a"].as_array().unwrap();
+        assert_eq!(array[0], toml::Value::Integer(1));
+        assert_eq!(array[1], toml::Value::Boolean(true));
         assert_eq!(
-            result.unwrap_err(),
-            Error::Config {
-                message: "Failed to parse config:\ninvalid socket address syntax\nin `gateway.bind_address`\n".to_string()
-            }
+            array[2].as_table().unwrap()["inner"],
+            toml::Value::String("x".to_string())
+        );
+    }
+
+    /// `UninitializedConfig::read_toml_config` dispatches on file extension: `.yaml`/`.yml` and
+    /// `.json` are converted via `convert_yaml_value`/`convert_json_value`, anything else
+    /// (including no extension) is parsed directly as TOML.
+    #[test]
+    fn test_read_toml_config_dispatches_by_extension() {
+        let dir = std::env::temp_dir().join(format!(
+            "tensorzero_test_read_toml_config_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let yaml_path = dir.join("config.yaml");
+        std::fs::write(&yaml_path, "gateway:\n  bind_address: \"0.0.0.0:3000\"\n").unwrap();
+        let yaml_table =
+            UninitializedConfig::read_toml_config(yaml_path.to_str().unwrap()).unwrap();
+        assert_eq!(
+            yaml_table["gateway"]["bind_address"].as_str(),
+            Some("0.0.0.0:3000")
         );
+
+        let json_path = dir.join("config.json");
+        std::fs::write(&json_path, r#"{"gateway": {"bind_address": "0.0.0.0:3001"}}"#).unwrap();
+        let json_table =
+            UninitializedConfig::read_toml_config(json_path.to_str().unwrap()).unwrap();
+        assert_eq!(
+            json_table["gateway"]["bind_address"].as_str(),
+            Some("0.0.0.0:3001")
+        );
+
+        let toml_path = dir.join("config.toml");
+        std::fs::write(&toml_path, "[gateway]\nbind_address = \"0.0.0.0:3002\"\n").unwrap();
+        let toml_table =
+            UninitializedConfig::read_toml_config(toml_path.to_str().unwrap()).unwrap();
+        assert_eq!(
+            toml_table["gateway"]["bind_address"].as_str(),
+            Some("0.0.0.0:3002")
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    /// Ensure that the config-item registry rewrites the deprecated `strict_json_schema` tool
+    /// alias to the canonical `strict` field before deserialization
+    #[test]
+    fn test_config_item_registry_rewrites_tool_alias() {
+        let mut config = get_sample_valid_config();
+        config["tools"]["get_temperature"]
+            .as_table_mut()
+            .unwrap()
+            .insert("strict_json_schema".to_string(), true.into());
+        let base_path = PathBuf::new();
+        let parsed_config =
+            Config::load_from_toml(config, base_path).expect("Failed to load config");
+        assert!(parsed_config.tools.get("get_temperature").unwrap().strict);
     }
 
     /// Ensure that the config parsing fails when the `[models]` section is missing
@@ -874,6 +3955,35 @@ mod tests {
         ));
     }
 
+    /// Ensure that `output_schema` (and the other schema fields) can be given as an inline TOML
+    /// table instead of a path to a schema file on disk.
+    #[test]
+    fn test_config_inline_schema() {
+        let mut config = get_sample_valid_config();
+        let mut inline_schema = toml::Table::new();
+        inline_schema.insert("type".to_string(), "object".into());
+        let mut properties = toml::Table::new();
+        let mut answer = toml::Table::new();
+        answer.insert("type".to_string(), "string".into());
+        properties.insert("answer".to_string(), toml::Value::Table(answer));
+        inline_schema.insert("properties".to_string(), toml::Value::Table(properties));
+        inline_schema.insert(
+            "required".to_string(),
+            toml::Value::Array(vec!["answer".into()]),
+        );
+
+        config["functions"]["json_with_schemas"]
+            .as_table_mut()
+            .expect("Failed to get `functions.json_with_schemas` section")
+            .insert(
+                "output_schema".to_string(),
+                toml::Value::Table(inline_schema),
+            );
+        let base_path = PathBuf::new();
+        Config::load_from_toml(config, base_path)
+            .expect("Failed to load config with inline output_schema");
+    }
+
     /// Ensure that the config parsing fails when there are extra variables for variants
     #[test]
     fn test_config_from_toml_table_extra_variables_variants() {
@@ -1182,6 +4292,193 @@ mod tests {
         );
     }
 
+    /// Ensure that the config validation fails when `tool_choice = "specific"` names a tool that
+    /// isn't in the function's `tools` list
+    #[test]
+    fn test_config_validate_tool_choice_specific_not_in_tools() {
+        let mut config = get_sample_valid_config();
+        config["functions"]["weather_helper"]["tool_choice"] =
+            toml::Value::Table(toml::Table::from_iter([(
+                "specific".to_string(),
+                toml::Value::String("non_existent_tool".to_string()),
+            )]));
+        let base_path = PathBuf::new();
+        let result = Config::load_from_toml(config, base_path);
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Config {
+                message: "Invalid Config: `functions.weather_helper.tool_choice`: tool `non_existent_tool` is not present in `functions.weather_helper.tools`".to_string()
+            }
+        );
+    }
+
+    /// Ensure that `get_model` falls back to a `[model_patterns.*]` entry on an exact-match miss
+    #[test]
+    fn test_config_get_model_falls_back_to_pattern() {
+        let mut config = get_sample_valid_config();
+
+        let mut entry = toml::Table::new();
+        entry.insert("priority".to_string(), 1.into());
+        entry.insert(
+            "config".to_string(),
+            toml::Value::Table(sample_model_pattern_config("openai")),
+        );
+        let mut model_patterns = toml::Table::new();
+        model_patterns.insert("openai::*".to_string(), toml::Value::Table(entry));
+        config.insert(
+            "model_patterns".to_string(),
+            toml::Value::Table(model_patterns),
+        );
+
+        let base_path = PathBuf::new();
+        let config = Config::load_from_toml(config, base_path).expect("Failed to load config");
+
+        // Exact matches are unaffected
+        assert!(config.get_model("gpt-3.5-turbo").is_ok());
+        // A miss falls back to the matching pattern
+        assert!(config.get_model("openai::gpt-4o").is_ok());
+        // A name that doesn't match any pattern still errors
+        assert!(config.get_model("anthropic::claude").is_err());
+    }
+
+    /// Ensure that `get_tool` falls back to a `[tool_patterns.*]` entry on an exact-match miss,
+    /// and that two distinct names matching the same pattern each get a `StaticToolConfig` with
+    /// their own name substituted in, rather than both getting the pattern string baked in.
+    #[test]
+    fn test_config_get_tool_falls_back_to_pattern_with_substituted_name() {
+        let mut config = get_sample_valid_config();
+
+        let mut inline_schema = toml::Table::new();
+        inline_schema.insert("type".to_string(), "object".into());
+        inline_schema.insert("properties".to_string(), toml::Value::Table(toml::Table::new()));
+
+        let mut tool_config = toml::Table::new();
+        tool_config.insert(
+            "description".to_string(),
+            "A tool defined generically for any `dynamic::*` name".into(),
+        );
+        tool_config.insert("parameters".to_string(), toml::Value::Table(inline_schema));
+
+        let mut entry = toml::Table::new();
+        entry.insert("priority".to_string(), 1.into());
+        entry.insert("config".to_string(), toml::Value::Table(tool_config));
+
+        let mut tool_patterns = toml::Table::new();
+        tool_patterns.insert("dynamic::*".to_string(), toml::Value::Table(entry));
+        config.insert("tool_patterns".to_string(), toml::Value::Table(tool_patterns));
+
+        let base_path = PathBuf::new();
+        let config = Config::load_from_toml(config, base_path).expect("Failed to load config");
+
+        // Exact matches are unaffected
+        assert!(config.get_tool("get_temperature").is_ok());
+        // A name that doesn't match any pattern still errors
+        assert!(config.get_tool("static::unrelated").is_err());
+
+        // Two different names matching the same pattern each get their own requested name, not
+        // the pattern string `dynamic::*`.
+        let first = config
+            .get_tool("dynamic::alpha")
+            .expect("Failed to resolve `dynamic::alpha` via tool_patterns");
+        let second = config
+            .get_tool("dynamic::beta")
+            .expect("Failed to resolve `dynamic::beta` via tool_patterns");
+        assert_ne!(first.name, second.name);
+        assert_eq!(first.name, "dynamic::alpha");
+        assert_eq!(second.name, "dynamic::beta");
+    }
+
+    /// A `strict = true` tool gets a compiled `ToolGrammar` matching its schema; a non-`strict`
+    /// tool (the sample config's `get_temperature`) gets none.
+    #[test]
+    fn test_config_tool_grammar_compiled_only_for_strict_tools() {
+        let mut config = get_sample_valid_config();
+
+        let mut properties = toml::Table::new();
+        let mut location_property = toml::Table::new();
+        location_property.insert("type".to_string(), "string".into());
+        properties.insert("location".to_string(), toml::Value::Table(location_property));
+
+        let mut inline_schema = toml::Table::new();
+        inline_schema.insert("type".to_string(), "object".into());
+        inline_schema.insert("properties".to_string(), toml::Value::Table(properties));
+
+        let mut strict_tool = toml::Table::new();
+        strict_tool.insert(
+            "description".to_string(),
+            "A strict tool with a grammar-constrained schema".into(),
+        );
+        strict_tool.insert("parameters".to_string(), toml::Value::Table(inline_schema));
+        strict_tool.insert("strict".to_string(), true.into());
+        let mut tools = config
+            .get("tools")
+            .and_then(|value| value.as_table())
+            .cloned()
+            .unwrap_or_default();
+        tools.insert("strict_tool".to_string(), toml::Value::Table(strict_tool));
+        config.insert("tools".to_string(), toml::Value::Table(tools));
+
+        let base_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let config = Config::load_from_toml(config, base_path).expect("Failed to load config");
+
+        let grammar = config
+            .tool_grammar("strict_tool")
+            .expect("Expected a compiled grammar for the `strict` tool");
+        assert_eq!(grammar.schema["type"], "object");
+        assert_eq!(grammar.schema["properties"]["location"]["type"], "string");
+
+        assert!(config.tool_grammar("get_temperature").is_none());
+    }
+
+    /// Build a `[config]` sub-table for a `model_patterns`/`model` entry with a single provider.
+    fn sample_model_pattern_config(provider_type: &str) -> toml::Table {
+        let mut provider = toml::Table::new();
+        provider.insert("type".to_string(), provider_type.into());
+        provider.insert("model_name".to_string(), "gpt-4o".into());
+
+        let mut providers = toml::Table::new();
+        providers.insert(provider_type.to_string(), toml::Value::Table(provider));
+
+        let mut config = toml::Table::new();
+        config.insert(
+            "routing".to_string(),
+            toml::Value::Array(vec![provider_type.into()]),
+        );
+        config.insert("providers".to_string(), toml::Value::Table(providers));
+        config
+    }
+
+    /// Ensure that the config validation rejects two model patterns sharing a priority
+    #[test]
+    fn test_config_validate_duplicate_pattern_priority() {
+        let mut config = get_sample_valid_config();
+
+        let mut make_entry = |provider_type: &str| {
+            let mut entry = toml::Table::new();
+            entry.insert("priority".to_string(), 1.into());
+            entry.insert(
+                "config".to_string(),
+                toml::Value::Table(sample_model_pattern_config(provider_type)),
+            );
+            toml::Value::Table(entry)
+        };
+
+        let mut model_patterns = toml::Table::new();
+        model_patterns.insert("openai::*".to_string(), make_entry("openai"));
+        model_patterns.insert("anthropic::*".to_string(), make_entry("anthropic"));
+        config.insert(
+            "model_patterns".to_string(),
+            toml::Value::Table(model_patterns),
+        );
+
+        let base_path = PathBuf::new();
+        let result = Config::load_from_toml(config, base_path);
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("multiple patterns share priority 1"));
+    }
+
     /// Ensure that get_templates returns the correct templates
     #[test]
     fn test_get_all_templates() {
@@ -1260,7 +4557,14 @@ mod tests {
 
     /// Get a sample valid config for testing
     fn get_sample_valid_config() -> toml::Table {
-        let config_str = r#"
+        toml::from_str(get_sample_valid_config_str()).expect("Failed to parse sample config")
+    }
+
+    /// The raw TOML backing [`get_sample_valid_config`], for tests that need an actual file on
+    /// disk (e.g. [`Config::set`]/[`Config::update_configuration`], which read/write a real
+    /// `config_path` rather than an in-memory `toml::Table`).
+    fn get_sample_valid_config_str() -> &'static str {
+        r#"
         # ┌────────────────────────────────────────────────────────────────────────────┐
         # │                                  GENERAL                                   │
         # └────────────────────────────────────────────────────────────────────────────┘
@@ -1411,9 +4715,67 @@ mod tests {
         [tools.get_temperature]
         description = "Get the weather for a given location"
         parameters = "fixtures/config/tools/get_temperature.json"
-        "#;
+        "#
+    }
+
+    #[test]
+    fn test_compiled_cache_key_changes_with_source_and_mtime() {
+        let dir = std::env::temp_dir().join(format!(
+            "tensorzero_test_compiled_cache_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let schema_path = dir.join("schema.json");
+        std::fs::write(&schema_path, r#"{"type": "object"}"#).unwrap();
+
+        let key_a = compiled_cache::compute_cache_key("config a", &[schema_path.clone()]).unwrap();
+        let key_b = compiled_cache::compute_cache_key("config b", &[schema_path.clone()]).unwrap();
+        assert_ne!(key_a, key_b, "different TOML source should change the cache key");
+
+        let key_a_again =
+            compiled_cache::compute_cache_key("config a", &[schema_path.clone()]).unwrap();
+        assert_eq!(key_a, key_a_again, "identical inputs should hash identically");
+
+        // Touch the referenced file so its mtime changes, which should invalidate the key.
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        std::fs::write(&schema_path, r#"{"type": "object", "extra": true}"#).unwrap();
+        let key_a_after_touch =
+            compiled_cache::compute_cache_key("config a", &[schema_path.clone()]).unwrap();
+        assert_ne!(
+            key_a, key_a_after_touch,
+            "touching a referenced file should change the cache key"
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_compiled_cache_round_trip() {
+        let dir = std::env::temp_dir().join(format!(
+            "tensorzero_test_compiled_cache_round_trip_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cache_path = dir.join("config.cache");
+
+        let summary = compiled_cache::CachedConfigSummary {
+            gateway_bind_address: Some("0.0.0.0:3000".to_string()),
+            gateway_disable_observability: false,
+            models: vec!["gpt-3.5-turbo".to_string()],
+            functions: vec!["generate_draft".to_string()],
+            tools: vec!["get_temperature".to_string()],
+            template_partials: vec!["header".to_string()],
+        };
+
+        compiled_cache::save(&cache_path, "abc123", &summary).unwrap();
+
+        let loaded = compiled_cache::load(&cache_path, "abc123");
+        assert_eq!(loaded, Some(summary));
+
+        // A stale key must never hand back the cached data.
+        assert_eq!(compiled_cache::load(&cache_path, "different-key"), None);
 
-        toml::from_str(config_str).expect("Failed to parse sample config")
+        std::fs::remove_dir_all(&dir).ok();
     }
 
     #[test]